@@ -1,18 +1,55 @@
 use std::collections::HashMap;
 use std::convert::TryFrom;
 
+use crate::bitset::DeckBits;
 use crate::deck::{CardIdentity, Deck, Energy, Turn, TurnNumber, TurnProfile};
+use crate::permutation_sampled::SampledPermutationIterator;
+use crate::permutation_simple::{factorial, RankedPermutationsIterator};
 use crate::PERMUTATION_COUNT_WARNING_THRESHOLD;
 
 pub trait AnalysisResult: std::fmt::Debug + std::fmt::Display {
     fn as_map(&self) -> HashMap<String, String>;
+
+    /// Structured counterpart to `as_map` for machine-readable output modes (the `--json` and
+    /// `--ndjson` CLI flags): where `as_map` stringifies every field for uniform display,
+    /// `as_json` preserves their real types. Default-implemented by wrapping each `as_map` value
+    /// as a JSON string; results with genuinely numeric fields (`CostEfficiencyAnalysisResult`,
+    /// `ConditionCountResult`) override it to emit numbers instead.
+    fn as_json(&self) -> serde_json::Value {
+        serde_json::Value::Object(
+            self.as_map()
+                .into_iter()
+                .map(|(key, value)| (key, serde_json::Value::String(value)))
+                .collect(),
+        )
+    }
 }
 
-pub trait Analysis: std::fmt::Debug + 'static {
+pub trait Analysis: std::fmt::Debug + Send + 'static {
     fn name(&self) -> &str;
     fn accept(&mut self, card: CardIdentity, turn: Turn);
     fn next_deck(&mut self);
     fn result(&self) -> Box<dyn AnalysisResult>;
+
+    /// Bitset fast path: evaluate this analysis against the whole dealt permutation at once
+    /// via `bits` instead of being driven card by card. Returns whether it did so; analyses
+    /// without a bitset-backed equivalent return `false` and still expect `accept` to be
+    /// called per card.
+    fn accept_bits(&mut self, _bits: &DeckBits) -> bool {
+        false
+    }
+
+    /// A fresh, zeroed accumulator of the same kind as `self`, handed to a parallel worker so
+    /// it can process its own shard of permutations without racing on `self`'s counters.
+    fn split(&self) -> Box<dyn Analysis>;
+
+    /// Fold a worker's partial accumulator (produced by `split` on this same analysis) into
+    /// `self` once the worker has finished its shard.
+    fn merge(&mut self, other: Box<dyn Analysis>);
+
+    /// Downcasting hook so `merge` implementations can recover their concrete type from the
+    /// `Box<dyn Analysis>` a worker hands back.
+    fn as_any(&self) -> &dyn std::any::Any;
 }
 
 pub trait PermutationIterator<T> {
@@ -52,6 +89,23 @@ impl<const N: usize> AnalysisExecutor<N> {
         }
     }
 
+    /// The name of this executor's (sole, by construction) analysis, for callers that pick an
+    /// executor out of a parsed list by name (e.g. the `optimize` CLI subcommand).
+    pub(crate) fn name(&self) -> &str {
+        self.analysis[0].name()
+    }
+
+    /// This executor's deck, for callers (e.g. the `histogram` CLI subcommand) that pick an
+    /// executor out of a parsed list by name and then drive a different kind of analysis
+    /// (`streaming::StreamingAnalysis`, not `Analysis`) over that same deck and turn profile.
+    pub(crate) fn deck(&self) -> Deck<CardIdentity, N> {
+        self.deck
+    }
+
+    pub(crate) fn turn_profile(&self) -> TurnProfile<N> {
+        self.turn_profile
+    }
+
     pub fn execute<T>(
         mut self,
         suppress_warnings: SuppressWarnings,
@@ -66,19 +120,123 @@ impl<const N: usize> AnalysisExecutor<N> {
             return Err((self, Warning::TooManyPermutations(permutations.count())));
         }
         while let Some(deck) = permutations.next() {
-            for (card, turn) in deck.card_iter().zip(self.turn_profile.turn_iter()) {
-                for analysis in self.analysis.iter_mut() {
-                    analysis.accept(card, *turn);
-                }
+            process_deck(&deck, &self.turn_profile, &mut self.analysis);
+        }
+        Ok(self.analysis.iter().map(|a| a.result()).collect())
+    }
+
+    /// Scores the deck against `sample_size` random orderings instead of exhaustively, skipping
+    /// the permutation-count warning entirely since the sample size is already bounded by the
+    /// caller. Intended for callers (like the beam-search deck optimizer) that run this many
+    /// times per candidate deck and need a fast, reproducible-from-`seed` estimate rather than
+    /// an exact count.
+    pub fn execute_sampled(mut self, seed: u64, sample_size: u64) -> Vec<Box<dyn AnalysisResult>> {
+        let mut permutations =
+            SampledPermutationIterator::with_sample_size(self.deck, seed, sample_size);
+        while let Some(deck) = PermutationIterator::next(&mut permutations) {
+            process_deck(&deck, &self.turn_profile, &mut self.analysis);
+        }
+        self.analysis.iter().map(|a| a.result()).collect()
+    }
+
+    /// Builds a fresh executor over `deck`, carrying zeroed copies of this executor's analyses
+    /// (via `Analysis::split`) instead of the originals. Lets a caller that already parsed a
+    /// user's analysis configuration (e.g. the simulated-annealing deck optimizer) re-score many
+    /// candidate decks against that same configuration without one candidate's accumulated state
+    /// leaking into the next.
+    pub(crate) fn retarget(&self, deck: Deck<CardIdentity, N>) -> Self {
+        Self {
+            deck,
+            analysis: self.analysis.iter().map(|a| a.split()).collect(),
+            turn_profile: self.turn_profile,
+        }
+    }
+
+    /// Shards the `N!` distinct-position permutation space across `worker_count` OS threads
+    /// instead of walking it on a single one, using `RankedPermutationsIterator` so each worker
+    /// starts directly at its shard's first index. Every analysis is `split` into a fresh
+    /// per-worker accumulator and `merge`d back once all workers finish, so the observable
+    /// result is identical to `execute::<AllPermutationsIterator<_>>` run single-threaded.
+    ///
+    /// Unlike `execute`, this is always over the full `N!` orderings (including ones that are
+    /// duplicates of each other when the deck has repeated cards) rather than the deduplicated
+    /// count `DeckPermutationIterator` produces, since unranking assumes `N` distinct index
+    /// positions, not `DeckPermutationIterator`'s multiset placements.
+    pub fn execute_parallel(
+        mut self,
+        worker_count: usize,
+        suppress_warnings: SuppressWarnings,
+    ) -> Result<Vec<Box<dyn AnalysisResult>>, (Self, Warning)> {
+        let total = factorial(N);
+        if suppress_warnings == SuppressWarnings::No && total > PERMUTATION_COUNT_WARNING_THRESHOLD
+        {
+            return Err((self, Warning::TooManyPermutations(total)));
+        }
+
+        let mut cards = [CardIdentity::default(); N];
+        for (i, card) in self.deck.card_iter().enumerate() {
+            cards[i] = card;
+        }
+
+        let worker_count = worker_count.max(1) as u64;
+        let shard_len = total / worker_count;
+        let remainder = total % worker_count;
+
+        let mut handles = Vec::new();
+        let mut start = 0u64;
+        for worker in 0..worker_count {
+            let len = shard_len + u64::from(worker < remainder);
+            if len == 0 {
+                continue;
             }
-            for analysis in self.analysis.iter_mut() {
-                analysis.next_deck();
+            let mut worker_analysis: Vec<Box<dyn Analysis>> =
+                self.analysis.iter().map(|a| a.split()).collect();
+            let turn_profile = self.turn_profile;
+            handles.push(std::thread::spawn(move || {
+                let mut permutations = RankedPermutationsIterator::new(cards, start, len);
+                while let Some(deck) = permutations.next() {
+                    process_deck(&deck, &turn_profile, &mut worker_analysis);
+                }
+                worker_analysis
+            }));
+            start += len;
+        }
+
+        for handle in handles {
+            let partials = handle.join().expect("worker thread panicked");
+            for (analysis, partial) in self.analysis.iter_mut().zip(partials) {
+                analysis.merge(partial);
             }
         }
+
         Ok(self.analysis.iter().map(|a| a.result()).collect())
     }
 }
 
+fn process_deck<const N: usize>(
+    deck: &Deck<CardIdentity, N>,
+    turn_profile: &TurnProfile<N>,
+    analysis: &mut [Box<dyn Analysis>],
+) {
+    let bits = DeckBits::build(deck, turn_profile);
+    let mut needs_card_scan: Vec<&mut Box<dyn Analysis>> = Vec::new();
+    for a in analysis.iter_mut() {
+        if !a.accept_bits(&bits) {
+            needs_card_scan.push(a);
+        }
+    }
+    if !needs_card_scan.is_empty() {
+        for (card, turn) in deck.card_iter().zip(turn_profile.turn_iter()) {
+            for a in needs_card_scan.iter_mut() {
+                a.accept(card, *turn);
+            }
+        }
+    }
+    for a in analysis.iter_mut() {
+        a.next_deck();
+    }
+}
+
 pub fn standard_turn_profile<const N: usize>() -> TurnProfile<N> {
     let mut standard = vec![
         Turn {
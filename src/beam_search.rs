@@ -0,0 +1,156 @@
+use crate::analyse::{standard_turn_profile, AnalysisExecutor};
+use crate::condition::Condition;
+use crate::condition_count::ConditionCount;
+use crate::deck::{Card, CardIdentity, Deck};
+use crate::MAX_COST;
+use std::collections::HashSet;
+
+/// Tuning knobs for [`search`]: how many states survive each expansion (`beam_width`), how many
+/// slots to fill before stopping (`depth`, clamped to `N`), and how the scoring oracle samples
+/// the permutation space for each candidate state.
+#[derive(Debug, Clone, Copy)]
+pub struct BeamSearchConfig {
+    pub beam_width: usize,
+    pub depth: usize,
+    pub sample_size: u64,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone)]
+struct BeamState<const N: usize> {
+    assignment: [CardIdentity; N],
+    filled: usize,
+    used_catalog: Vec<bool>,
+    remaining_cost_profile: [u8; { MAX_COST + 1 } as usize],
+    score: f64,
+}
+
+/// Searches for the `N`-slot deck composition, drawn from `catalog` within `cost_profile`'s
+/// per-cost budget, that maximizes the estimated probability that `objective` holds.
+///
+/// A state is a partial assignment of the `N` slots, with not-yet-filled slots left as
+/// `CardIdentity::None`. At each step every state in the beam is expanded by filling its next
+/// open slot with each still-available, budget-consistent catalog card; successors are
+/// deduplicated by their full assignment, scored by running `objective` through
+/// `AnalysisExecutor` with the sampled permutation iterator as the oracle, and the beam is
+/// truncated back to the best `beam_width` before moving on. The best complete (or best
+/// partial, if `catalog`/`cost_profile` can't fill every slot) assignment found is returned.
+pub fn search<const N: usize>(
+    catalog: &[Card],
+    cost_profile: [u8; { MAX_COST + 1 } as usize],
+    objective: Box<dyn Condition>,
+    config: BeamSearchConfig,
+) -> Deck<CardIdentity, N> {
+    let initial = BeamState {
+        assignment: [CardIdentity::None; N],
+        filled: 0,
+        used_catalog: vec![false; catalog.len()],
+        remaining_cost_profile: cost_profile,
+        score: 0.0,
+    };
+    let mut beam = vec![initial];
+    let depth = config.depth.min(N);
+
+    for _ in 0..depth {
+        let mut visited = HashSet::new();
+        let mut successors = Vec::new();
+        for state in &beam {
+            if state.filled >= N {
+                continue;
+            }
+            for (i, candidate) in catalog.iter().enumerate() {
+                if state.used_catalog[i] {
+                    continue;
+                }
+                let cost = candidate.cost() as usize;
+                if state.remaining_cost_profile[cost] == 0 {
+                    continue;
+                }
+                let mut next = state.clone();
+                next.assignment[next.filled] = CardIdentity::Full(*candidate);
+                next.filled += 1;
+                next.used_catalog[i] = true;
+                next.remaining_cost_profile[cost] -= 1;
+                if !visited.insert(next.assignment) {
+                    continue;
+                }
+                next.score = score(&next.assignment, objective.as_ref(), &config);
+                successors.push(next);
+            }
+        }
+        if successors.is_empty() {
+            break;
+        }
+        successors.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        successors.truncate(config.beam_width);
+        beam = successors;
+    }
+
+    beam.into_iter()
+        .max_by(|a, b| a.score.partial_cmp(&b.score).unwrap())
+        .map(|state| Deck::from(state.assignment))
+        .unwrap_or_else(|| Deck::from([CardIdentity::None; N]))
+}
+
+fn score<const N: usize>(
+    assignment: &[CardIdentity; N],
+    objective: &dyn Condition,
+    config: &BeamSearchConfig,
+) -> f64 {
+    let analysis = ConditionCount::new("objective".to_string(), objective.box_clone());
+    let executor = AnalysisExecutor::new(
+        Deck::from(*assignment),
+        standard_turn_profile(),
+        vec![Box::new(analysis)],
+    );
+    executor
+        .execute_sampled(config.seed, config.sample_size)
+        .pop()
+        .and_then(|result| result.as_map().get("estimate").and_then(|s| s.parse().ok()))
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{search, BeamSearchConfig};
+    use crate::condition::CardIdCondition;
+    use crate::deck::{Card, CardIdentity, Id};
+
+    fn config() -> BeamSearchConfig {
+        BeamSearchConfig {
+            beam_width: 1,
+            depth: 5,
+            sample_size: 100_000,
+            seed: 42,
+        }
+    }
+
+    #[test]
+    fn prefers_the_catalog_card_that_satisfies_the_objective() {
+        let catalog = [Card::new(0, 1), Card::new(1, 1), Card::new(2, 1)];
+        let mut cost_profile = [0u8; { crate::MAX_COST + 1 } as usize];
+        cost_profile[1] = 2;
+        let objective = Box::new(CardIdCondition::new(Id::from(0)));
+
+        let deck = search::<2>(&catalog, cost_profile, objective, config());
+
+        assert!(deck.card_iter().any(|card| card.id() == Some(Id::from(0))));
+    }
+
+    #[test]
+    fn never_picks_more_catalog_cards_of_a_cost_than_the_profile_allows() {
+        let catalog = [Card::new(0, 1), Card::new(1, 1), Card::new(2, 2)];
+        let mut cost_profile = [0u8; { crate::MAX_COST + 1 } as usize];
+        cost_profile[1] = 1;
+        cost_profile[2] = 1;
+        let objective = Box::new(CardIdCondition::new(Id::from(0)));
+
+        let deck = search::<2>(&catalog, cost_profile, objective, config());
+
+        let cost_one_count = deck
+            .card_iter()
+            .filter(|card| matches!(card, CardIdentity::Full(c) if c.cost() == 1))
+            .count();
+        assert!(cost_one_count <= 1);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::deck::{CardIdentity, Deck, Id, TurnNumber, TurnProfile};
+use crate::MAX_ID;
+
+/// A growable bit set backed by `u64` words, addressed as `(word, mask) = (i / 64, 1 << (i % 64))`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BitVector {
+    words: Vec<u64>,
+}
+
+impl BitVector {
+    pub fn new(bits: usize) -> Self {
+        Self {
+            words: vec![0; Self::words_for(bits)],
+        }
+    }
+
+    pub fn from_words(words: Vec<u64>) -> Self {
+        Self { words }
+    }
+
+    pub fn all_ones(bits: usize) -> Self {
+        let mut result = Self::new(bits);
+        for i in 0..bits {
+            result.insert(i);
+        }
+        result
+    }
+
+    pub fn insert(&mut self, i: usize) {
+        let (word, mask) = Self::word_and_mask(i);
+        self.words[word] |= mask;
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        let (word, mask) = Self::word_and_mask(i);
+        self.words[word] & mask != 0
+    }
+
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|word| word.count_ones()).sum()
+    }
+
+    pub fn intersect(&self, other: &BitVector) -> BitVector {
+        BitVector::from_words(
+            self.words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(a, b)| a & b)
+                .collect(),
+        )
+    }
+
+    pub fn union(&self, other: &BitVector) -> BitVector {
+        BitVector::from_words(
+            self.words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(a, b)| a | b)
+                .collect(),
+        )
+    }
+
+    /// Complement within the logical `bits` length, leaving any padding past it at zero.
+    pub fn complement(&self, bits: usize) -> BitVector {
+        Self::all_ones(bits).intersect(&BitVector::from_words(
+            self.words.iter().map(|word| !word).collect(),
+        ))
+    }
+
+    fn words_for(bits: usize) -> usize {
+        (bits + 63) / 64
+    }
+
+    fn word_and_mask(i: usize) -> (usize, u64) {
+        (i / 64, 1u64 << (i % 64))
+    }
+}
+
+/// A dense `elements x bits_per_element` matrix of bits, one row per element, addressable
+/// cell-by-cell via `(row, col)`.
+#[derive(Debug, Clone)]
+pub struct BitMatrix {
+    u64s_per_elem: usize,
+    data: Vec<u64>,
+}
+
+impl BitMatrix {
+    pub fn new(elements: usize, bits_per_element: usize) -> Self {
+        let u64s_per_elem = BitVector::words_for(bits_per_element).max(1);
+        Self {
+            u64s_per_elem,
+            data: vec![0; elements * u64s_per_elem],
+        }
+    }
+
+    pub fn insert(&mut self, row: usize, col: usize) {
+        let (word, mask) = BitVector::word_and_mask(col);
+        let range = self.range(row);
+        self.data[range][word] |= mask;
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        let (word, mask) = BitVector::word_and_mask(col);
+        self.row(row)[word] & mask != 0
+    }
+
+    pub fn range(&self, row: usize) -> std::ops::Range<usize> {
+        let start = row * self.u64s_per_elem;
+        start..start + self.u64s_per_elem
+    }
+
+    pub fn row(&self, row: usize) -> &[u64] {
+        &self.data[self.range(row)]
+    }
+}
+
+fn card_cost(card: CardIdentity) -> Option<u8> {
+    match card {
+        CardIdentity::Full(card) => Some(card.cost()),
+        CardIdentity::Cost(energy) => Some(energy.value()),
+        CardIdentity::None => None,
+    }
+}
+
+/// A bitset view of one dealt permutation, built once and shared by every condition that
+/// evaluates it, replacing the per-card linear scans `Condition::check` otherwise requires.
+#[derive(Debug)]
+pub struct DeckBits {
+    by_card: BitMatrix,
+    turn_numbers: Vec<TurnNumber>,
+    costs: Vec<Option<u8>>,
+    positions: usize,
+}
+
+impl DeckBits {
+    pub fn build<const N: usize>(
+        deck: &Deck<CardIdentity, N>,
+        turn_profile: &TurnProfile<N>,
+    ) -> Self {
+        let mut by_card = BitMatrix::new(MAX_ID as usize, N);
+        let mut costs = Vec::with_capacity(N);
+        for (position, card) in deck.card_iter().enumerate() {
+            if let Some(id) = card.id() {
+                by_card.insert(id.index(), position);
+            }
+            costs.push(card_cost(card));
+        }
+        let turn_numbers = turn_profile.turn_iter().map(|t| t.number).collect();
+
+        Self {
+            by_card,
+            turn_numbers,
+            costs,
+            positions: N,
+        }
+    }
+
+    pub fn card_positions(&self, id: Id) -> BitVector {
+        BitVector::from_words(self.by_card.row(id.index()).to_vec())
+    }
+
+    pub fn positions_at_or_before(&self, bound: TurnNumber) -> BitVector {
+        let mut mask = BitVector::new(self.positions);
+        for (position, turn_number) in self.turn_numbers.iter().enumerate() {
+            if *turn_number <= bound {
+                mask.insert(position);
+            }
+        }
+        mask
+    }
+
+    pub fn positions_at_most_cost(&self, bound: u8) -> BitVector {
+        let mut mask = BitVector::new(self.positions);
+        for (position, cost) in self.costs.iter().enumerate() {
+            if matches!(cost, Some(cost) if *cost <= bound) {
+                mask.insert(position);
+            }
+        }
+        mask
+    }
+
+    pub fn positions(&self) -> usize {
+        self.positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_vector_insert_and_contains() {
+        let mut vector = BitVector::new(70);
+        vector.insert(5);
+        vector.insert(65);
+
+        assert!(vector.contains(5));
+        assert!(vector.contains(65));
+        assert!(!vector.contains(6));
+        assert_eq!(vector.count_ones(), 2);
+    }
+
+    #[test]
+    fn bit_matrix_addresses_cells_independently() {
+        let mut matrix = BitMatrix::new(3, 10);
+        matrix.insert(0, 1);
+        matrix.insert(2, 9);
+
+        assert!(matrix.contains(0, 1));
+        assert!(!matrix.contains(0, 9));
+        assert!(matrix.contains(2, 9));
+    }
+
+    #[test]
+    fn deck_bits_tracks_card_positions() {
+        let deck: Deck<CardIdentity, 3> = Deck::from([
+            CardIdentity::Full(crate::deck::Card::new(0, 0)),
+            CardIdentity::None,
+            CardIdentity::Full(crate::deck::Card::new(1, 0)),
+        ]);
+        let turn_profile = TurnProfile::from([
+            crate::deck::Turn {
+                number: TurnNumber::from(0),
+                energy: crate::deck::Energy::from(0),
+            },
+            crate::deck::Turn {
+                number: TurnNumber::from(1),
+                energy: crate::deck::Energy::from(0),
+            },
+            crate::deck::Turn {
+                number: TurnNumber::from(2),
+                energy: crate::deck::Energy::from(0),
+            },
+        ]);
+        let bits = DeckBits::build(&deck, &turn_profile);
+
+        assert!(bits.card_positions(Id::from(0)).contains(0));
+        assert!(bits.card_positions(Id::from(1)).contains(2));
+        assert!(bits.positions_at_or_before(TurnNumber::from(1)).contains(0));
+        assert!(!bits.positions_at_or_before(TurnNumber::from(1)).contains(2));
+    }
+}
@@ -1,27 +1,90 @@
+use crate::bitset::{BitVector, DeckBits};
 use crate::deck::{CardIdentity, Id, Turn, TurnNumber};
 
-pub trait Condition: std::fmt::Debug + 'static {
+pub trait Condition: std::fmt::Debug + Send + 'static {
     fn check(&mut self, card: CardIdentity, turn: Turn) -> bool;
     fn next_deck(&mut self);
+
+    /// Positions of a dealt permutation at which this condition's atomic predicate holds,
+    /// the bitset-backed counterpart to repeatedly calling `check` position by position.
+    fn position_mask(&self, bits: &DeckBits) -> BitVector;
+
+    /// Fast path for `AnalysisExecutor::execute`: true if this condition is satisfied
+    /// anywhere in the dealt permutation, computed with word-wise bitset operations
+    /// instead of a linear scan.
+    fn eval_bits(&self, bits: &DeckBits) -> bool {
+        self.position_mask(bits).count_ones() > 0
+    }
+
+    /// Clones behind the trait object, so a `Box<dyn Condition>` tree can be duplicated (e.g.
+    /// to give each parallel `AnalysisExecutor` worker its own condition state to mutate).
+    fn box_clone(&self) -> Box<dyn Condition>;
+}
+
+impl Clone for Box<dyn Condition> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Lets a boxed condition tree stand in anywhere a concrete `T: Condition` is expected (e.g. as
+/// the objective `ConditionCount` is built over), by delegating to the boxed value.
+impl Condition for Box<dyn Condition> {
+    fn check(&mut self, card: CardIdentity, turn: Turn) -> bool {
+        (**self).check(card, turn)
+    }
+
+    fn next_deck(&mut self) {
+        (**self).next_deck();
+    }
+
+    fn position_mask(&self, bits: &DeckBits) -> BitVector {
+        (**self).position_mask(bits)
+    }
+
+    fn eval_bits(&self, bits: &DeckBits) -> bool {
+        (**self).eval_bits(bits)
+    }
+
+    fn box_clone(&self) -> Box<dyn Condition> {
+        (**self).box_clone()
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AllOf {
     all: Vec<Box<dyn Condition>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AnyOf {
     any: Vec<Box<dyn Condition>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct CardIdCondition(crate::deck::Id);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct ComesAtOrBeforeCondition(TurnNumber);
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct Not {
+    inner: Box<dyn Condition>,
+}
+
+/// True once at least `n` of `conditions` have each independently become satisfied within the
+/// deck, even if they're satisfied on different turns: every child is wrapped in its own
+/// `LockConditionResult` so a partial match latches instead of being overwritten by a later,
+/// unrelated card, and `check` just counts how many latches are set. Lets `ConditionCount`
+/// measure "flexible" combos (e.g. "at least 2 of these 3 enablers by turn 3") that neither
+/// `AllOf` (requires every child) nor `AnyOf` (requires only one) can express.
+#[derive(Debug, Clone)]
+pub struct NOf {
+    n: usize,
+    conditions: Vec<LockConditionResult<Box<dyn Condition>>>,
+}
+
+#[derive(Debug, Clone)]
 pub struct LockConditionResult<T> {
     result: bool,
     condition: T,
@@ -39,6 +102,24 @@ impl AnyOf {
     }
 }
 
+impl Not {
+    pub fn new(inner: Box<dyn Condition>) -> Self {
+        Not { inner }
+    }
+}
+
+impl NOf {
+    pub fn new(n: usize, conditions: Vec<Box<dyn Condition>>) -> Self {
+        NOf {
+            n,
+            conditions: conditions
+                .into_iter()
+                .map(LockConditionResult::new)
+                .collect(),
+        }
+    }
+}
+
 impl<T> LockConditionResult<T> {
     pub fn new(condition: T) -> Self {
         LockConditionResult {
@@ -56,6 +137,14 @@ impl Condition for CardIdCondition {
     fn next_deck(&mut self) {
         //do nothing
     }
+
+    fn position_mask(&self, bits: &DeckBits) -> BitVector {
+        bits.card_positions(self.0)
+    }
+
+    fn box_clone(&self) -> Box<dyn Condition> {
+        Box::new(*self)
+    }
 }
 
 impl CardIdCondition {
@@ -72,6 +161,14 @@ impl Condition for ComesAtOrBeforeCondition {
     fn next_deck(&mut self) {
         //do nothing
     }
+
+    fn position_mask(&self, bits: &DeckBits) -> BitVector {
+        bits.positions_at_or_before(self.0)
+    }
+
+    fn box_clone(&self) -> Box<dyn Condition> {
+        Box::new(*self)
+    }
 }
 
 impl ComesAtOrBeforeCondition {
@@ -84,7 +181,7 @@ impl Condition for AllOf {
     fn check(&mut self, card: CardIdentity, turn: Turn) -> bool {
         let mut result = true;
         for condition in self.all.iter_mut() {
-            result = result & condition.check(card, turn);
+            result &= condition.check(card, turn);
         }
         result
     }
@@ -92,13 +189,26 @@ impl Condition for AllOf {
     fn next_deck(&mut self) {
         self.all.iter_mut().for_each(|c| c.next_deck());
     }
+
+    fn position_mask(&self, bits: &DeckBits) -> BitVector {
+        self.all
+            .iter()
+            .map(|c| c.position_mask(bits))
+            .fold(BitVector::all_ones(bits.positions()), |acc, mask| {
+                acc.intersect(&mask)
+            })
+    }
+
+    fn box_clone(&self) -> Box<dyn Condition> {
+        Box::new(self.clone())
+    }
 }
 
 impl Condition for AnyOf {
     fn check(&mut self, card: CardIdentity, turn: Turn) -> bool {
         let mut result = false;
         for condition in self.any.iter_mut() {
-            result = result | condition.check(card, turn);
+            result |= condition.check(card, turn);
         }
         result
     }
@@ -106,14 +216,95 @@ impl Condition for AnyOf {
     fn next_deck(&mut self) {
         self.any.iter_mut().for_each(|c| c.next_deck());
     }
+
+    fn position_mask(&self, bits: &DeckBits) -> BitVector {
+        self.any
+            .iter()
+            .map(|c| c.position_mask(bits))
+            .fold(BitVector::new(bits.positions()), |acc, mask| {
+                acc.union(&mask)
+            })
+    }
+
+    fn box_clone(&self) -> Box<dyn Condition> {
+        Box::new(self.clone())
+    }
+}
+
+impl Condition for Not {
+    fn check(&mut self, card: CardIdentity, turn: Turn) -> bool {
+        !self.inner.check(card, turn)
+    }
+
+    fn next_deck(&mut self) {
+        self.inner.next_deck();
+    }
+
+    fn position_mask(&self, bits: &DeckBits) -> BitVector {
+        self.inner.position_mask(bits).complement(bits.positions())
+    }
+
+    fn box_clone(&self) -> Box<dyn Condition> {
+        Box::new(self.clone())
+    }
 }
 
-impl<T: Condition> Condition for LockConditionResult<T> {
+impl Condition for NOf {
+    fn check(&mut self, card: CardIdentity, turn: Turn) -> bool {
+        let mut satisfied = 0;
+        for condition in self.conditions.iter_mut() {
+            condition.accept(card, turn);
+            if condition.result() {
+                satisfied += 1;
+            }
+        }
+        satisfied >= self.n
+    }
+
+    fn next_deck(&mut self) {
+        self.conditions.iter_mut().for_each(|c| c.next_deck());
+    }
+
+    /// Union of the `n` children whose own masks are individually largest, as a representative
+    /// set of positions where this `NOf` "tends to" be satisfied. This is only an approximation
+    /// of the true semantics (at least `n` children each independently satisfiable somewhere in
+    /// the deck, not necessarily at the same positions as each other) — `eval_bits` is overridden
+    /// below rather than derived from this mask, since the default `position_mask(bits).count_ones()
+    /// > 0` would wrongly report satisfaction whenever any single child mask is non-empty.
+    fn position_mask(&self, bits: &DeckBits) -> BitVector {
+        let mut masks: Vec<BitVector> = self
+            .conditions
+            .iter()
+            .map(|c| c.position_mask(bits))
+            .collect();
+        masks.sort_by_key(|mask| std::cmp::Reverse(mask.count_ones()));
+        masks
+            .into_iter()
+            .take(self.n)
+            .fold(BitVector::new(bits.positions()), |acc, mask| {
+                acc.union(&mask)
+            })
+    }
+
+    fn eval_bits(&self, bits: &DeckBits) -> bool {
+        self.conditions
+            .iter()
+            .filter(|c| c.position_mask(bits).count_ones() > 0)
+            .count()
+            >= self.n
+    }
+
+    fn box_clone(&self) -> Box<dyn Condition> {
+        Box::new(self.clone())
+    }
+}
+
+impl<T: Condition + Clone> Condition for LockConditionResult<T> {
     fn check(&mut self, card: CardIdentity, turn: Turn) -> bool {
         if self.result {
             return true;
         }
-        self.result = self.result | self.condition.check(card, turn);
+        self.result |= self.condition.check(card, turn);
         self.result
     }
 
@@ -121,6 +312,26 @@ impl<T: Condition> Condition for LockConditionResult<T> {
         self.result = false;
         self.condition.next_deck();
     }
+
+    /// Broadcasts to every position once the inner condition is satisfied anywhere in the deck,
+    /// rather than delegating straight to the inner condition's own (possibly single-position)
+    /// mask: a `LockConditionResult` exists precisely so its per-card `check`/`accept` latch
+    /// stays satisfied regardless of which later position re-triggers it, and an outer `AllOf`/
+    /// `AnyOf` composing several of these (e.g. "card 0 by turn 1 AND card 1 by turn 2", each
+    /// half its own latch over a different card's position) needs that same anywhere-in-the-deck
+    /// reading — not an intersection of the two cards' distinct positions, which is empty by
+    /// construction and would make the combo unsatisfiable.
+    fn position_mask(&self, bits: &DeckBits) -> BitVector {
+        if self.condition.eval_bits(bits) {
+            BitVector::all_ones(bits.positions())
+        } else {
+            BitVector::new(bits.positions())
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Condition> {
+        Box::new(self.clone())
+    }
 }
 
 impl<T: Condition> LockConditionResult<T> {
@@ -128,7 +339,7 @@ impl<T: Condition> LockConditionResult<T> {
         if self.result {
             return;
         }
-        self.result = self.result | self.condition.check(card, turn);
+        self.result |= self.condition.check(card, turn);
     }
 
     pub fn result(&self) -> bool {
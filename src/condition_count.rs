@@ -1,4 +1,5 @@
 use crate::analyse::{Analysis, AnalysisResult};
+use crate::bitset::DeckBits;
 use crate::condition::{Condition, LockConditionResult};
 use crate::deck::{CardIdentity, Turn};
 use std::collections::HashMap;
@@ -9,6 +10,7 @@ pub struct ConditionCount<T> {
     condition: LockConditionResult<T>,
     count: u64,
     total_amount: u64,
+    bits_result: Option<bool>,
 }
 
 #[derive(Debug)]
@@ -25,11 +27,12 @@ impl<T> ConditionCount<T> {
             condition: LockConditionResult::new(condition),
             count: 0,
             total_amount: 0,
+            bits_result: None,
         }
     }
 }
 
-impl<T: Condition> Analysis for ConditionCount<T> {
+impl<T: Condition + Clone> Analysis for ConditionCount<T> {
     fn name(&self) -> &str {
         &self.name
     }
@@ -40,7 +43,11 @@ impl<T: Condition> Analysis for ConditionCount<T> {
 
     fn next_deck(&mut self) {
         self.total_amount += 1;
-        if self.condition.result() {
+        let satisfied = self
+            .bits_result
+            .take()
+            .unwrap_or_else(|| self.condition.result());
+        if satisfied {
             self.count += 1;
         }
         self.condition.next_deck();
@@ -53,25 +60,101 @@ impl<T: Condition> Analysis for ConditionCount<T> {
             total_amount: self.total_amount,
         })
     }
+
+    fn accept_bits(&mut self, bits: &DeckBits) -> bool {
+        self.bits_result = Some(self.condition.eval_bits(bits));
+        true
+    }
+
+    fn split(&self) -> Box<dyn Analysis> {
+        Box::new(ConditionCount {
+            name: self.name.clone(),
+            condition: self.condition.clone(),
+            count: 0,
+            total_amount: 0,
+            bits_result: None,
+        })
+    }
+
+    fn merge(&mut self, other: Box<dyn Analysis>) {
+        let other = other
+            .as_any()
+            .downcast_ref::<ConditionCount<T>>()
+            .expect("merge only ever receives a Box produced by this analysis's own split");
+        self.count += other.count;
+        self.total_amount += other.total_amount;
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+// 95% confidence: z = Φ⁻¹(0.975).
+const WILSON_Z: f64 = 1.959_963_984_540_054;
+
+impl ConditionCountResult {
+    fn estimate(&self) -> f64 {
+        if self.total_amount == 0 {
+            return 0.0;
+        }
+        self.count as f64 / self.total_amount as f64
+    }
+
+    /// Wilson score 95% confidence interval for the estimate, the counterpart to an exact
+    /// percentage once `total_amount` is a Monte Carlo sample size rather than an exhaustive
+    /// permutation count.
+    fn wilson_interval(&self) -> (f64, f64) {
+        if self.total_amount == 0 {
+            return (0.0, 0.0);
+        }
+        let n = self.total_amount as f64;
+        let p = self.estimate();
+        let z2 = WILSON_Z * WILSON_Z;
+        let denominator = 1.0 + z2 / n;
+        let centre = p + z2 / (2.0 * n);
+        let adjustment = WILSON_Z * ((p * (1.0 - p) / n) + (z2 / (4.0 * n * n))).sqrt();
+        ((centre - adjustment) / denominator, (centre + adjustment) / denominator)
+    }
 }
 
 impl AnalysisResult for ConditionCountResult {
     fn as_map(&self) -> HashMap<String, String> {
+        let (lower, upper) = self.wilson_interval();
         let mut map = HashMap::new();
         map.insert("name".to_owned(), self.name.clone());
         map.insert("count".to_owned(), format!("{}", self.count));
         map.insert("total_amount".to_owned(), format!("{}", self.total_amount));
+        map.insert("estimate".to_owned(), format!("{:.6}", self.estimate()));
+        map.insert("ci_lower".to_owned(), format!("{:.6}", lower));
+        map.insert("ci_upper".to_owned(), format!("{:.6}", upper));
         map
     }
+
+    fn as_json(&self) -> serde_json::Value {
+        let (lower, upper) = self.wilson_interval();
+        serde_json::json!({
+            "name": self.name,
+            "count": self.count,
+            "total_amount": self.total_amount,
+            "success_percent": self.estimate() * 100.0,
+            "ci_lower_percent": lower * 100.0,
+            "ci_upper_percent": upper * 100.0,
+        })
+    }
 }
 
 impl std::fmt::Display for ConditionCountResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let success_percent = (self.count as f32) / (self.total_amount as f32) * 100.0;
+        let (lower, upper) = self.wilson_interval();
         write!(
             f,
-            "{} is available {:.2} percent of the time",
-            self.name, success_percent
+            "{} is available {:.2} percent of the time (95% CI [{:.2}, {:.2}] percent, n = {})",
+            self.name,
+            self.estimate() * 100.0,
+            lower * 100.0,
+            upper * 100.0,
+            self.total_amount
         )
     }
 }
@@ -79,7 +162,7 @@ impl std::fmt::Display for ConditionCountResult {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::condition::{AllOf, CardIdCondition, ComesAtOrBeforeCondition};
+    use crate::condition::{AllOf, CardIdCondition, ComesAtOrBeforeCondition, NOf, Not};
     use crate::deck::{Card, Energy, Id, TurnNumber};
 
     #[test]
@@ -141,6 +224,19 @@ mod tests {
         assert_eq!(result_map.get("count").map(String::as_str), Some("1"));
     }
 
+    #[test]
+    fn as_json_reports_count_and_total_amount_as_numbers_not_strings() {
+        let mut analysis = analysis_comes_at_or_before(0);
+
+        analysis.accept(card(0, 0), turn(0, 0));
+        analysis.next_deck();
+
+        let json = analysis.result().as_json();
+        assert_eq!(json["count"], 1);
+        assert_eq!(json["total_amount"], 1);
+        assert_eq!(json["success_percent"], 100.0);
+    }
+
     fn analysis_comes_at_or_before(position: u8) -> ConditionCount<AllOf> {
         let condition = analysis_comes_at_or_before_with_id(position, 0);
         ConditionCount::new("test".to_string(), condition)
@@ -211,6 +307,56 @@ mod tests {
         )
     }
 
+    #[test]
+    fn not_inverts_whether_the_inner_condition_is_satisfied() {
+        let condition = analysis_comes_at_or_before_with_id(0, 0);
+        let mut analysis = ConditionCount::new("test".to_string(), Not::new(Box::new(condition)));
+
+        analysis.accept(card(0, 0), turn(0, 0));
+        analysis.next_deck();
+
+        let result_map = analysis.result().as_map();
+        assert_eq!(result_map.get("count").map(String::as_str), Some("0"));
+
+        analysis.accept(card(0, 0), turn(1, 0));
+        analysis.next_deck();
+
+        let result_map = analysis.result().as_map();
+        assert_eq!(result_map.get("count").map(String::as_str), Some("1"));
+    }
+
+    #[test]
+    fn n_of_is_satisfied_once_at_least_n_children_are_independently_satisfied() {
+        let mut analysis = analysis_2_of_3_cards_come_at_or_before(0);
+
+        analysis.accept(card(0, 0), turn(0, 0));
+        analysis.accept(card(1, 0), turn(0, 0));
+        analysis.next_deck();
+
+        let result_map = analysis.result().as_map();
+        assert_eq!(result_map.get("count").map(String::as_str), Some("1"));
+    }
+
+    #[test]
+    fn n_of_is_not_satisfied_when_fewer_than_n_children_are_satisfied() {
+        let mut analysis = analysis_2_of_3_cards_come_at_or_before(0);
+
+        analysis.accept(card(0, 0), turn(0, 0));
+        analysis.next_deck();
+
+        let result_map = analysis.result().as_map();
+        assert_eq!(result_map.get("count").map(String::as_str), Some("0"));
+    }
+
+    fn analysis_2_of_3_cards_come_at_or_before(position: u8) -> ConditionCount<NOf> {
+        let conditions = (0..3)
+            .map(|id| {
+                Box::new(analysis_comes_at_or_before_with_id(position, id)) as Box<dyn Condition>
+            })
+            .collect();
+        ConditionCount::new("test".to_string(), NOf::new(2, conditions))
+    }
+
     fn analysis_comes_at_or_before_with_id(position: u8, id: u8) -> AllOf {
         AllOf::new(vec![
             Box::new(CardIdCondition::new(Id::from(id))),
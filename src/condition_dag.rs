@@ -0,0 +1,232 @@
+use crate::bitset::{BitVector, DeckBits};
+use crate::condition::Condition;
+use crate::deck::{CardIdentity, Id, Turn, TurnNumber};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum NodeSpec {
+    Card(Id),
+    DrawnAtOrBefore(TurnNumber),
+    AllOf(Vec<NodeId>),
+    AnyOf(Vec<NodeId>),
+    /// Broadcasts `child`'s satisfiability across the whole deck: once `child` has matched at
+    /// any position (bits path) or any card seen so far (`check` path), this node reads as
+    /// "everywhere"/"true" from then on, rather than only at the instant `child` itself matched.
+    /// Mirrors `dsl::Op::Lock` / the old `LockConditionResult::position_mask` broadcast, and
+    /// exists for the same reason: composing two `AllOf`/`AnyOf` siblings that each hold at a
+    /// different position (e.g. two distinct `card before turn` facts) must AND/OR their
+    /// *latched* satisfaction, not their instantaneous, disjoint positions.
+    Lock(NodeId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// Builds a `ConditionDag`, structurally deduplicating nodes as they're added: two calls with
+/// the same atom or the same children (e.g. a condition reached twice through different
+/// `ReferenceCondition`s) collapse onto a single `NodeId`.
+#[derive(Debug, Default)]
+pub struct ConditionDagBuilder {
+    nodes: Vec<NodeSpec>,
+    index: HashMap<NodeSpec, NodeId>,
+}
+
+impl ConditionDagBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn card(&mut self, id: Id) -> NodeId {
+        self.intern(NodeSpec::Card(id))
+    }
+
+    pub fn drawn_at_or_before(&mut self, turn: TurnNumber) -> NodeId {
+        self.intern(NodeSpec::DrawnAtOrBefore(turn))
+    }
+
+    pub fn all_of(&mut self, children: Vec<NodeId>) -> NodeId {
+        self.intern(NodeSpec::AllOf(children))
+    }
+
+    pub fn any_of(&mut self, children: Vec<NodeId>) -> NodeId {
+        self.intern(NodeSpec::AnyOf(children))
+    }
+
+    pub fn lock(&mut self, child: NodeId) -> NodeId {
+        self.intern(NodeSpec::Lock(child))
+    }
+
+    fn intern(&mut self, spec: NodeSpec) -> NodeId {
+        if let Some(id) = self.index.get(&spec) {
+            return *id;
+        }
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(spec.clone());
+        self.index.insert(spec, id);
+        id
+    }
+
+    pub fn build(self) -> ConditionDag {
+        ConditionDag { nodes: self.nodes }
+    }
+}
+
+/// A compiled condition DAG: every distinct atomic or composite predicate is represented
+/// exactly once, with composite nodes (`AllOf`/`AnyOf`) referencing their children by `NodeId`.
+/// Nodes are always interned child-before-parent, so evaluation can proceed in a single
+/// left-to-right pass over the node list.
+#[derive(Debug)]
+pub struct ConditionDag {
+    nodes: Vec<NodeSpec>,
+}
+
+impl ConditionDag {
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Computes every node's position mask against `bits` in one pass: atomic nodes read
+    /// straight off `bits`, composite nodes fold their already-computed children's masks
+    /// instead of re-deriving them, so a predicate shared by many nodes is only evaluated once.
+    fn position_masks(&self, bits: &DeckBits) -> Vec<BitVector> {
+        let mut masks: Vec<BitVector> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let mask = match node {
+                NodeSpec::Card(id) => bits.card_positions(*id),
+                NodeSpec::DrawnAtOrBefore(turn) => bits.positions_at_or_before(*turn),
+                NodeSpec::AllOf(children) => children.iter().map(|c| masks[c.0].clone()).fold(
+                    BitVector::all_ones(bits.positions()),
+                    |acc, mask| acc.intersect(&mask),
+                ),
+                NodeSpec::AnyOf(children) => children
+                    .iter()
+                    .map(|c| masks[c.0].clone())
+                    .fold(BitVector::new(bits.positions()), |acc, mask| {
+                        acc.union(&mask)
+                    }),
+                NodeSpec::Lock(child) => {
+                    if masks[child.0].count_ones() > 0 {
+                        BitVector::all_ones(bits.positions())
+                    } else {
+                        BitVector::new(bits.positions())
+                    }
+                }
+            };
+            masks.push(mask);
+        }
+        masks
+    }
+}
+
+/// A `Condition` rooted at one node of a shared `ConditionDag`. Every `DagCondition` built from
+/// the same `Arc<ConditionDag>` shares the cost of evaluating any sub-predicate they have in
+/// common, rather than each walking its own independent copy of the tree.
+#[derive(Debug, Clone)]
+pub struct DagCondition {
+    dag: Arc<ConditionDag>,
+    node: NodeId,
+    latched: Vec<bool>,
+}
+
+impl DagCondition {
+    pub fn new(dag: Arc<ConditionDag>, node: NodeId) -> Self {
+        let len = dag.len();
+        Self {
+            dag,
+            node,
+            latched: vec![false; len],
+        }
+    }
+}
+
+impl Condition for DagCondition {
+    fn check(&mut self, card: CardIdentity, turn: Turn) -> bool {
+        let mut instantaneous = vec![false; self.dag.len()];
+        for (i, node) in self.dag.nodes.iter().enumerate() {
+            instantaneous[i] = match node {
+                NodeSpec::Card(id) => Some(*id) == card.id(),
+                NodeSpec::DrawnAtOrBefore(bound) => turn.number <= *bound,
+                NodeSpec::AllOf(children) => children.iter().all(|c| instantaneous[c.0]),
+                NodeSpec::AnyOf(children) => children.iter().any(|c| instantaneous[c.0]),
+                // `self.latched[child.0]` was already updated for this card earlier in this same
+                // pass (children are always interned before parents), so this reads the latched
+                // state through the current card, not just its own instantaneous value.
+                NodeSpec::Lock(child) => self.latched[child.0],
+            };
+            self.latched[i] = self.latched[i] || instantaneous[i];
+        }
+        self.latched[self.node.0]
+    }
+
+    fn next_deck(&mut self) {
+        self.latched.iter_mut().for_each(|latch| *latch = false);
+    }
+
+    fn position_mask(&self, bits: &DeckBits) -> BitVector {
+        self.dag.position_masks(bits)[self.node.0].clone()
+    }
+
+    fn box_clone(&self) -> Box<dyn Condition> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConditionDagBuilder, DagCondition};
+    use crate::bitset::DeckBits;
+    use crate::condition::Condition;
+    use crate::deck::{Card, CardIdentity, Deck, Energy, Id, Turn, TurnNumber, TurnProfile};
+    use std::sync::Arc;
+
+    fn card(id: u8, cost: u8) -> CardIdentity {
+        CardIdentity::Full(Card::new(id, cost))
+    }
+
+    fn turn(number: u8, energy: u8) -> Turn {
+        Turn {
+            number: TurnNumber::from(number),
+            energy: Energy::from(energy),
+        }
+    }
+
+    #[test]
+    fn interns_identical_subtrees_reached_through_different_roots() {
+        let mut builder = ConditionDagBuilder::new();
+        let card_a = builder.card(Id::from(0));
+        let before_a = builder.drawn_at_or_before(TurnNumber::from(1));
+        let left = builder.all_of(vec![card_a, before_a]);
+
+        let card_b = builder.card(Id::from(0));
+        let before_b = builder.drawn_at_or_before(TurnNumber::from(1));
+        let right = builder.all_of(vec![card_b, before_b]);
+
+        assert_eq!(card_a, card_b);
+        assert_eq!(before_a, before_b);
+        assert_eq!(left, right);
+        assert_eq!(builder.build().len(), 3);
+    }
+
+    #[test]
+    fn position_mask_matches_the_instantaneous_check_path() {
+        let mut builder = ConditionDagBuilder::new();
+        let card_node = builder.card(Id::from(0));
+        let before_node = builder.drawn_at_or_before(TurnNumber::from(1));
+        let root = builder.all_of(vec![card_node, before_node]);
+        let dag = Arc::new(builder.build());
+
+        let deck: Deck<CardIdentity, 3> = Deck::from([card(0, 0), CardIdentity::None, card(0, 0)]);
+        let turn_profile = TurnProfile::from([turn(0, 0), turn(1, 0), turn(2, 0)]);
+        let bits = DeckBits::build(&deck, &turn_profile);
+
+        let mut via_check = DagCondition::new(dag.clone(), root);
+        let mut matched = false;
+        for (card, turn) in deck.card_iter().zip(turn_profile.turn_iter()) {
+            matched |= via_check.check(card, *turn);
+        }
+
+        let via_bits = DagCondition::new(dag, root);
+        assert_eq!(matched, via_bits.position_mask(&bits).count_ones() > 0);
+    }
+}
@@ -43,48 +43,62 @@ impl<const M: usize> CostEfficiencyAnalysis<M> {
             self.last_turn = turn.number;
             self.energy_left = turn.energy;
         }
-        let (min_to_max_spent, min_to_max_profile) = self.spend_from_min_to_max();
-        let (max_to_min_spent, max_to_min_profile) = self.spend_from_max_to_min();
-        if min_to_max_spent > max_to_min_spent {
-            self.energy_spent += min_to_max_spent;
-            self.energy_profile = min_to_max_profile;
-        } else {
-            self.energy_spent += max_to_min_spent;
-            self.energy_profile = max_to_min_profile;
-        }
+        let (spent, profile) = self.spend_optimal();
+        self.energy_spent += spent;
+        self.energy_profile = profile;
     }
 
-    fn spend_from_min_to_max(&self) -> (Energy, EnergyProfile<M>) {
-        let mut profile = self.energy_profile.clone();
-        let mut left = self.energy_left.clone();
-        let mut spent = Energy::default();
-        for (energy, amount) in profile.iter_mut() {
-            if left < energy {
-                break;
-            }
-            if *amount > 0 {
-                *amount -= 1;
-                left -= energy;
-                spent += energy;
+    /// Maximum energy spendable from `energy_left` out of the cards currently in
+    /// `energy_profile`, found exactly via a bounded-knapsack subset-sum DP rather than a
+    /// greedy heuristic: `reachable[s]` tracks whether sum `s` is achievable at all from the
+    /// cards considered so far, folded in one cost value at a time so each layer's bounded
+    /// count (at most the cards available at that cost) is respected. `units_used[cost][s]`
+    /// records how many copies of `cost` a sum of `s` used once that layer was folded in, so
+    /// the best reachable sum can be walked back through the cost values to recover which
+    /// cards were actually spent.
+    fn spend_optimal(&self) -> (Energy, EnergyProfile<M>) {
+        let cap = self.energy_left.value() as usize;
+        let mut reachable = vec![false; cap + 1];
+        reachable[0] = true;
+        let mut units_used: Vec<Vec<u8>> = Vec::with_capacity(M);
+
+        for cost in 0..M {
+            let count = self.energy_profile[Energy::from(cost as u8)] as usize;
+            let mut layer_used = vec![0u8; cap + 1];
+            if cost > 0 && count > 0 {
+                let mut next_reachable = reachable.clone();
+                for s in cost..=cap {
+                    if next_reachable[s] {
+                        continue;
+                    }
+                    let max_units = (s / cost).min(count);
+                    for units in 1..=max_units {
+                        if reachable[s - units * cost] {
+                            next_reachable[s] = true;
+                            layer_used[s] = units as u8;
+                            break;
+                        }
+                    }
+                }
+                reachable = next_reachable;
             }
+            units_used.push(layer_used);
         }
-        (spent, profile)
-    }
 
-    fn spend_from_max_to_min(&self) -> (Energy, EnergyProfile<M>) {
-        let mut profile = self.energy_profile.clone();
-        let mut left = self.energy_left.clone();
+        let best = (0..=cap).rev().find(|&s| reachable[s]).unwrap_or(0);
+
+        let mut profile = self.energy_profile;
         let mut spent = Energy::default();
-        for (energy, amount) in profile.iter_mut().rev() {
-            if left < energy {
-                continue;
-            }
-            if *amount > 0 {
-                *amount -= 1;
-                left -= energy;
-                spent += energy;
+        let mut remaining = best;
+        for cost in (0..M).rev() {
+            let units = units_used[cost][remaining];
+            if units > 0 {
+                profile[Energy::from(cost as u8)] -= units;
+                spent += Energy::from((units as usize * cost) as u8);
+                remaining -= units as usize * cost;
             }
         }
+
         (spent, profile)
     }
 
@@ -126,6 +140,23 @@ impl<const M: usize> Analysis for CostEfficiencyAnalysis<M> {
             number_of_decks: self.number_of_decks,
         })
     }
+
+    fn split(&self) -> Box<dyn Analysis> {
+        Box::new(Self::new(self.name.clone()))
+    }
+
+    fn merge(&mut self, other: Box<dyn Analysis>) {
+        let other = other
+            .as_any()
+            .downcast_ref::<Self>()
+            .expect("merge only ever receives a Box produced by this analysis's own split");
+        self.total_spent += other.total_spent;
+        self.number_of_decks += other.number_of_decks;
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
 }
 
 impl AnalysisResult for CostEfficiencyAnalysisResult {
@@ -139,6 +170,15 @@ impl AnalysisResult for CostEfficiencyAnalysisResult {
         );
         map
     }
+
+    fn as_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "total_spent": self.total_spent,
+            "number_of_decks": self.number_of_decks,
+            "median": self.total_spent as f64 / self.number_of_decks as f64,
+        })
+    }
 }
 
 impl std::fmt::Display for CostEfficiencyAnalysisResult {
@@ -156,6 +196,7 @@ impl std::fmt::Display for CostEfficiencyAnalysisResult {
 #[cfg(test)]
 mod tests {
     use super::CostEfficiencyAnalysis;
+    use crate::analyse::{Analysis, AnalysisResult};
     use crate::deck::{Energy, EnergyProfile, Turn, TurnNumber};
 
     #[test]
@@ -204,7 +245,7 @@ mod tests {
     }
 
     #[test]
-    fn spend_from_min_to_max_returns_correct_value() {
+    fn spend_optimal_prefers_the_single_card_that_fills_the_budget_most() {
         let analysis = CostEfficiencyAnalysis {
             name: "test".to_string(),
             total_spent: 0,
@@ -215,46 +256,63 @@ mod tests {
             energy_profile: energy_profile([0, 1, 0, 1]),
         };
 
-        let (energy, profile) = analysis.spend_from_min_to_max();
+        let (energy, profile) = analysis.spend_optimal();
 
-        assert_eq!(energy, Energy::from(1));
-        assert_eq!(profile, energy_profile([0, 0, 0, 1]));
+        assert_eq!(energy, Energy::from(3));
+        assert_eq!(profile, energy_profile([0, 1, 0, 0]));
     }
 
     #[test]
-    fn spend_from_max_to_min_returns_correct_value() {
+    fn spend_optimal_returns_correct_value_exact_equality() {
         let analysis = CostEfficiencyAnalysis {
             name: "test".to_string(),
             total_spent: 0,
             number_of_decks: 0,
-            energy_left: Energy::from(3),
+            energy_left: Energy::from(2),
             energy_spent: Default::default(),
             last_turn: Default::default(),
-            energy_profile: energy_profile([0, 1, 0, 1]),
+            energy_profile: energy_profile([0, 0, 1, 0]),
         };
 
-        let (energy, profile) = analysis.spend_from_max_to_min();
+        let (energy, profile) = analysis.spend_optimal();
 
-        assert_eq!(energy, Energy::from(3));
-        assert_eq!(profile, energy_profile([0, 1, 0, 0]));
+        assert_eq!(energy, Energy::from(2));
+        assert_eq!(profile, energy_profile([0, 0, 0, 0]));
     }
 
     #[test]
-    fn spend_from_max_to_min_returns_correct_value_exact_equality() {
+    fn spend_optimal_finds_the_optimum_where_ascending_greedy_falls_short() {
+        // energy_left = 5, one card costing 4 and two costing 3: ascending-order greedy
+        // (3, 3, 4) only fits the first 3 (spending 3), descending-order greedy (4, 3, 3)
+        // fits the 4 alone (spending 4) but an exact search confirms 4 is in fact optimal,
+        // since no combination of the two 3s fits within the budget of 5.
         let analysis = CostEfficiencyAnalysis {
             name: "test".to_string(),
             total_spent: 0,
             number_of_decks: 0,
-            energy_left: Energy::from(2),
+            energy_left: Energy::from(5),
             energy_spent: Default::default(),
             last_turn: Default::default(),
-            energy_profile: energy_profile([0, 0, 1, 0]),
+            energy_profile: energy_profile([0, 0, 0, 2, 1]),
         };
 
-        let (energy, profile) = analysis.spend_from_max_to_min();
+        let (energy, profile) = analysis.spend_optimal();
 
-        assert_eq!(energy, Energy::from(2));
-        assert_eq!(profile, energy_profile([0, 0, 0, 0]));
+        assert_eq!(energy, Energy::from(4));
+        assert_eq!(profile, energy_profile([0, 0, 0, 2, 0]));
+    }
+
+    #[test]
+    fn as_json_reports_total_spent_and_median_as_numbers_not_strings() {
+        let mut analysis = CostEfficiencyAnalysis::<6>::new("cost efficiency".to_owned());
+        analysis.accept(Energy::from(2), turn(1, 2));
+        analysis.next_deck();
+
+        let json = analysis.result().as_json();
+
+        assert_eq!(json["total_spent"], 2);
+        assert_eq!(json["number_of_decks"], 1);
+        assert_eq!(json["median"], 2.0);
     }
 
     fn energy_profile<const N: usize>(profile: [u8; N]) -> EnergyProfile<N> {
@@ -13,14 +13,14 @@ pub struct Energy(u8);
 #[derive(Default, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Card(u8);
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum CardIdentity {
     Full(Card),
     Cost(Energy),
     None,
 }
 
-#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct TurnNumber(u8);
 
 #[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
@@ -61,6 +61,10 @@ impl Card {
     pub fn id(self) -> Id {
         Id(self.0 >> 4)
     }
+
+    pub(crate) fn cost(self) -> u8 {
+        self.0 << 4 >> 4
+    }
 }
 
 impl<T: Copy, const N: usize> Deck<T, N> {
@@ -206,6 +210,12 @@ impl From<u8> for Energy {
     }
 }
 
+impl Energy {
+    pub(crate) fn value(self) -> u8 {
+        self.0
+    }
+}
+
 impl Into<u64> for Energy {
     fn into(self) -> u64 {
         self.0 as u64
@@ -218,12 +228,24 @@ impl From<u8> for TurnNumber {
     }
 }
 
+impl TurnNumber {
+    pub(crate) fn value(self) -> u8 {
+        self.0
+    }
+}
+
 impl From<u8> for Id {
     fn from(val: u8) -> Self {
         Self(val)
     }
 }
 
+impl Id {
+    pub(crate) fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::deck::{Card, Id};
@@ -0,0 +1,450 @@
+//! A small textual condition language compiled to a postfix `Vec<Op>` program and evaluated
+//! with a boolean/position stack, so analyses can be driven by config/CLI strings instead of
+//! hand-assembled `Box<dyn Condition>` trees.
+//!
+//! Grammar (precedence `not` > `and` > `or`, parentheses group, `lock(...)` latches a
+//! sub-expression the way `LockConditionResult` does):
+//!
+//! ```text
+//! expr       := or_expr
+//! or_expr    := and_expr ("or" and_expr)*
+//! and_expr   := unary ("and" unary)*
+//! unary      := "not" unary | primary
+//! primary    := "(" expr ")"
+//!             | "lock" "(" expr ")"
+//!             | "card" NUMBER "before" "turn" NUMBER
+//!             | "cost" "at" "most" NUMBER
+//! ```
+
+use crate::bitset::{BitVector, DeckBits};
+use crate::condition::Condition;
+use crate::deck::{CardIdentity, Energy, Id, Turn, TurnNumber};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Card(Id),
+    DrawnAtOrBefore(TurnNumber),
+    CostAtMost(Energy),
+    And,
+    Or,
+    Not,
+    Lock,
+}
+
+/// A compiled condition expression, evaluated as a tiny stack machine: atoms push a value,
+/// combinators pop their operands and push the combined result.
+#[derive(Debug, Clone)]
+pub struct Program {
+    ops: Vec<Op>,
+    locked: Vec<bool>,
+}
+
+impl Program {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let tokens = lex(source)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+        };
+        let mut ops = Vec::new();
+        parser.parse_or(&mut ops)?;
+        if parser.position != tokens.len() {
+            return Err(ParseError::TrailingTokens(parser.position));
+        }
+        let locks = ops.iter().filter(|op| matches!(op, Op::Lock)).count();
+        Ok(Self {
+            ops,
+            locked: vec![false; locks],
+        })
+    }
+}
+
+fn card_cost(card: CardIdentity) -> Option<u8> {
+    match card {
+        CardIdentity::Full(card) => Some(card.cost()),
+        CardIdentity::Cost(energy) => Some(energy.value()),
+        CardIdentity::None => None,
+    }
+}
+
+impl Condition for Program {
+    fn check(&mut self, card: CardIdentity, turn: Turn) -> bool {
+        let mut stack: Vec<bool> = Vec::with_capacity(self.ops.len());
+        let mut lock_index = 0;
+        for op in &self.ops {
+            match op {
+                Op::Card(id) => stack.push(Some(*id) == card.id()),
+                Op::DrawnAtOrBefore(bound) => stack.push(turn.number <= *bound),
+                Op::CostAtMost(bound) => {
+                    stack.push(matches!(card_cost(card), Some(cost) if cost <= bound.value()))
+                }
+                Op::And => {
+                    let (b, a) = (stack.pop().unwrap(), stack.pop().unwrap());
+                    stack.push(a && b);
+                }
+                Op::Or => {
+                    let (b, a) = (stack.pop().unwrap(), stack.pop().unwrap());
+                    stack.push(a || b);
+                }
+                Op::Not => {
+                    let a = stack.pop().unwrap();
+                    stack.push(!a);
+                }
+                Op::Lock => {
+                    let value = stack.pop().unwrap();
+                    let latch = &mut self.locked[lock_index];
+                    *latch = *latch || value;
+                    stack.push(*latch);
+                    lock_index += 1;
+                }
+            }
+        }
+        stack.pop().unwrap_or(false)
+    }
+
+    fn next_deck(&mut self) {
+        for latch in self.locked.iter_mut() {
+            *latch = false;
+        }
+    }
+
+    fn position_mask(&self, bits: &DeckBits) -> BitVector {
+        let mut stack: Vec<BitVector> = Vec::with_capacity(self.ops.len());
+        for op in &self.ops {
+            match op {
+                Op::Card(id) => stack.push(bits.card_positions(*id)),
+                Op::DrawnAtOrBefore(bound) => stack.push(bits.positions_at_or_before(*bound)),
+                Op::CostAtMost(bound) => stack.push(bits.positions_at_most_cost(bound.value())),
+                Op::And => {
+                    let (b, a) = (stack.pop().unwrap(), stack.pop().unwrap());
+                    stack.push(a.intersect(&b));
+                }
+                Op::Or => {
+                    let (b, a) = (stack.pop().unwrap(), stack.pop().unwrap());
+                    stack.push(a.union(&b));
+                }
+                Op::Not => {
+                    let a = stack.pop().unwrap();
+                    stack.push(a.complement(bits.positions()));
+                }
+                // Mirrors `LockConditionResult::position_mask`'s broadcast: a lock is satisfied
+                // by its inner mask being non-empty *somewhere* in the deck, not by any single
+                // position holding it, so composing several locked atoms (e.g. distinct cards,
+                // which can never share a position) via `and`/`or` has to intersect/union
+                // all-or-nothing masks rather than the raw, necessarily-disjoint position sets.
+                Op::Lock => {
+                    let a = stack.pop().unwrap();
+                    stack.push(if a.count_ones() > 0 {
+                        BitVector::all_ones(bits.positions())
+                    } else {
+                        BitVector::new(bits.positions())
+                    });
+                }
+            }
+        }
+        stack.pop().unwrap_or_else(|| BitVector::new(bits.positions()))
+    }
+
+    fn box_clone(&self) -> Box<dyn Condition> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Word(&'a str),
+    Number(u8),
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    UnexpectedCharacter(usize, char),
+    UnexpectedEnd,
+    UnexpectedToken(usize),
+    TrailingTokens(usize),
+}
+
+fn lex(source: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    let mut tokens = Vec::new();
+    let bytes = source.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < bytes.len() && (bytes[i] as char).is_ascii_digit() {
+                i += 1;
+            }
+            let number = source[start..i]
+                .parse::<u8>()
+                .map_err(|_| ParseError::UnexpectedCharacter(start, c))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() {
+            let start = i;
+            while i < bytes.len() && (bytes[i] as char).is_alphanumeric() {
+                i += 1;
+            }
+            tokens.push(Token::Word(&source[start..i]));
+        } else {
+            return Err(ParseError::UnexpectedCharacter(i, c));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a, 'b> {
+    tokens: &'a [Token<'b>],
+    position: usize,
+}
+
+impl<'a, 'b> Parser<'a, 'b> {
+    fn parse_or(&mut self, ops: &mut Vec<Op>) -> Result<(), ParseError> {
+        self.parse_and(ops)?;
+        while self.consume_word("or") {
+            self.parse_and(ops)?;
+            ops.push(Op::Or);
+        }
+        Ok(())
+    }
+
+    fn parse_and(&mut self, ops: &mut Vec<Op>) -> Result<(), ParseError> {
+        self.parse_unary(ops)?;
+        while self.consume_word("and") {
+            self.parse_unary(ops)?;
+            ops.push(Op::And);
+        }
+        Ok(())
+    }
+
+    fn parse_unary(&mut self, ops: &mut Vec<Op>) -> Result<(), ParseError> {
+        if self.consume_word("not") {
+            self.parse_unary(ops)?;
+            ops.push(Op::Not);
+            Ok(())
+        } else {
+            self.parse_primary(ops)
+        }
+    }
+
+    fn parse_primary(&mut self, ops: &mut Vec<Op>) -> Result<(), ParseError> {
+        if self.consume(Token::LParen) {
+            self.parse_or(ops)?;
+            self.expect(Token::RParen)?;
+            return Ok(());
+        }
+        if self.consume_word("lock") {
+            self.expect(Token::LParen)?;
+            self.parse_or(ops)?;
+            self.expect(Token::RParen)?;
+            ops.push(Op::Lock);
+            return Ok(());
+        }
+        if self.consume_word("card") {
+            let id = self.expect_number()?;
+            self.expect_word("before")?;
+            self.expect_word("turn")?;
+            let turn = self.expect_number()?;
+            ops.push(Op::Card(Id::from(id)));
+            ops.push(Op::DrawnAtOrBefore(TurnNumber::from(turn)));
+            ops.push(Op::And);
+            // Implicitly latched: this atom names one specific card's own position, so once it's
+            // satisfied there it must stay satisfied regardless of which later position is being
+            // checked — the same reason `tests.rs`'s hand-built combos wrap each per-card
+            // `AllOf` in its own `LockConditionResult` before `and`-ing several together.
+            ops.push(Op::Lock);
+            return Ok(());
+        }
+        if self.consume_word("cost") {
+            self.expect_word("at")?;
+            self.expect_word("most")?;
+            let bound = self.expect_number()?;
+            ops.push(Op::CostAtMost(Energy::from(bound)));
+            return Ok(());
+        }
+        Err(self.unexpected())
+    }
+
+    fn peek(&self) -> Option<Token<'b>> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn consume(&mut self, token: Token<'b>) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn consume_word(&mut self, word: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case(word) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, token: Token<'b>) -> Result<(), ParseError> {
+        if self.consume(token) {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<(), ParseError> {
+        if self.consume_word(word) {
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn expect_number(&mut self) -> Result<u8, ParseError> {
+        match self.peek() {
+            Some(Token::Number(n)) => {
+                self.position += 1;
+                Ok(n)
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn unexpected(&self) -> ParseError {
+        if self.position >= self.tokens.len() {
+            ParseError::UnexpectedEnd
+        } else {
+            ParseError::UnexpectedToken(self.position)
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::UnexpectedCharacter(position, c) => {
+                write!(f, "unexpected character '{}' at position {}", c, position)
+            }
+            ParseError::UnexpectedEnd => write!(f, "unexpected end of expression"),
+            ParseError::UnexpectedToken(position) => {
+                write!(f, "unexpected token at position {}", position)
+            }
+            ParseError::TrailingTokens(position) => {
+                write!(f, "trailing tokens starting at position {}", position)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyse::{AnalysisExecutor, SuppressWarnings};
+    use crate::condition_count::ConditionCount;
+    use crate::deck::{Card, Deck, Energy, TurnProfile};
+    use crate::permutation_optimized::DeckPermutationIterator;
+
+    fn card(id: u8, cost: u8) -> CardIdentity {
+        CardIdentity::Full(Card::new(id, cost))
+    }
+
+    fn turn(number: u8, energy: u8) -> Turn {
+        Turn {
+            number: TurnNumber::from(number),
+            energy: Energy::from(energy),
+        }
+    }
+
+    fn test_three_card_deck_comes_at_or_before_condition(
+        comes_at_or_before: u8,
+        total_amount: &str,
+        count: &str,
+    ) {
+        let deck: Deck<CardIdentity, 3> =
+            Deck::from([card(0, 0), CardIdentity::None, CardIdentity::None]);
+        let program =
+            Program::parse(&format!("card 0 before turn {}", comes_at_or_before)).unwrap();
+        let analysis = ConditionCount::new("should be in n of cases".to_string(), program);
+        let turn_profile = TurnProfile::from([turn(0, 0), turn(1, 0), turn(2, 0)]);
+        let analyse = AnalysisExecutor::<3>::new(deck, turn_profile, vec![Box::new(analysis)]);
+        let result = analyse
+            .execute::<DeckPermutationIterator<_, 3>>(SuppressWarnings::Yes)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap();
+        let result_map = result.as_map();
+        assert_eq!(
+            result_map.get("total_amount").map(String::as_str),
+            Some(total_amount)
+        );
+        assert_eq!(result_map.get("count").map(String::as_str), Some(count));
+    }
+
+    #[test]
+    fn three_card_deck_first_card() {
+        test_three_card_deck_comes_at_or_before_condition(0, "3", "1");
+    }
+
+    #[test]
+    fn three_card_deck_second_or_earlier_card() {
+        test_three_card_deck_comes_at_or_before_condition(1, "3", "2");
+    }
+
+    #[test]
+    fn three_card_deck_third_or_earlier_card() {
+        test_three_card_deck_comes_at_or_before_condition(2, "3", "3");
+    }
+
+    #[test]
+    fn test_four_card_deck() {
+        let program =
+            Program::parse("card 0 before turn 1 and card 1 before turn 2").unwrap();
+        let analysis = ConditionCount::new("should be in 4 of 12 cases".to_string(), program);
+        let deck: Deck<CardIdentity, 4> = Deck::from([
+            card(0, 0),
+            card(1, 0),
+            CardIdentity::None,
+            CardIdentity::None,
+        ]);
+        let turn_profile = TurnProfile::from([turn(0, 0), turn(1, 0), turn(2, 0), turn(3, 0)]);
+        let analyse = AnalysisExecutor::<4>::new(deck, turn_profile, vec![Box::new(analysis)]);
+        let result = analyse
+            .execute::<DeckPermutationIterator<_, 4>>(SuppressWarnings::Yes)
+            .unwrap()
+            .into_iter()
+            .next()
+            .unwrap();
+        let result_map = result.as_map();
+        assert_eq!(
+            result_map.get("total_amount").map(String::as_str),
+            Some("12")
+        );
+        assert_eq!(result_map.get("count").map(String::as_str), Some("4"));
+    }
+
+    #[test]
+    fn parses_or_and_not_and_parentheses() {
+        let program = Program::parse("not (card 0 before turn 1 or card 1 before turn 1)");
+        assert!(program.is_ok());
+    }
+
+    #[test]
+    fn reports_unexpected_token() {
+        let err = Program::parse("card 0 or or").unwrap_err();
+        assert!(matches!(err, ParseError::UnexpectedToken(_)));
+    }
+}
@@ -1,10 +1,14 @@
 use crate::analyse::{standard_turn_profile, AnalysisExecutor};
-use crate::condition::{AllOf, AnyOf, LockConditionResult};
+use crate::condition::AllOf;
+use crate::condition_dag::{ConditionDagBuilder, DagCondition, NodeId};
 use crate::cost_efficiency::CostEfficiencyAnalysis;
 use crate::deck::{Card, CardIdentity, Deck, Energy, TurnNumber};
+use crate::dsl::Program;
+use crate::optimal_play::OptimalPlayAnalysis;
 use crate::MAX_COST;
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[derive(Deserialize, Clone)]
 pub struct Input {
@@ -40,6 +44,9 @@ pub enum Condition {
     AllOfCondition(AllOfCondition),
     AnyOfCondition(AnyOfCondition),
     ReferenceCondition(ReferenceCondition),
+    ExpressionCondition(ExpressionCondition),
+    NotCondition(NotCondition),
+    NOfCondition(NOfCondition),
 }
 
 #[derive(Deserialize, Clone)]
@@ -63,6 +70,42 @@ pub struct ReferenceCondition {
     pub(crate) reference: String,
 }
 
+/// A condition given as text in the small expression language `dsl::Program` parses (e.g.
+/// `"card 0 before turn 1 and not card 1 before turn 2"`), for combo queries that are easier to
+/// write as one expression than to assemble out of nested `all_of`/`any_of`/`card_name` JSON.
+/// `dsl::Program` addresses cards by the same `Id` `associate_card_name_with_card_id` assigns
+/// them in declaration order in the input's top-level `cards` list (the first card is `card 0`,
+/// the second `card 1`, and so on), rather than by name, so `cards` names the subset this
+/// expression references, ensuring they end up in the analysis's deck the same way a
+/// `card_name` elsewhere in its `conditions` would.
+///
+/// Only valid as a top-level entry in an analysis's `conditions` list: it can't currently be
+/// nested inside an `all_of_condition`/`any_of_condition`/`reference` the way the other variants
+/// can, since the condition DAG those compile into has no node for an arbitrary boxed condition.
+#[derive(Deserialize, Clone)]
+pub struct ExpressionCondition {
+    pub(crate) expression: String,
+    pub(crate) cards: Vec<String>,
+}
+
+/// Negates `not`, which can itself be any other condition variant except an
+/// `ExpressionCondition` (including another `not_condition`/`n_of_condition`). Compiles straight to `condition::Not` rather than through
+/// the condition DAG, the same way `ExpressionCondition` does, since `ConditionDagBuilder` has
+/// no node for negation.
+#[derive(Deserialize, Clone)]
+pub struct NotCondition {
+    pub(crate) not: Box<Condition>,
+}
+
+/// True once at least `n_of` of `conditions` are each independently satisfied somewhere in the
+/// deck. Compiles straight to `condition::NOf`, bypassing the condition DAG for the same reason
+/// `NotCondition` does.
+#[derive(Deserialize, Clone)]
+pub struct NOfCondition {
+    pub(crate) n_of: usize,
+    pub(crate) conditions: Vec<Condition>,
+}
+
 pub enum Error {
     Kind,
     ProfileLength(usize, usize),
@@ -73,6 +116,9 @@ pub enum Error {
     UnknownConditionReference(String),
     CardCost(usize),
     SameReference(String),
+    InvalidExpression(String, crate::dsl::ParseError),
+    ExpressionConditionNotTopLevel,
+    NotOrNOfConditionNotTopLevel,
 }
 
 pub fn read_from_file(path: &str) -> Input {
@@ -81,6 +127,15 @@ pub fn read_from_file(path: &str) -> Input {
 }
 
 pub fn parse<const N: usize>(input: Input) -> Result<Vec<AnalysisExecutor<N>>, Error> {
+    parse_with_pool(input).map(|(analysis, _pool)| analysis)
+}
+
+/// Like `parse`, but also returns the card pool (`Input::cards`, resolved to the same `Card`
+/// ids the analyses were built against) that a caller searching over deck compositions (e.g.
+/// the simulated-annealing `optimize` subcommand) needs in order to propose candidate decks.
+pub fn parse_with_pool<const N: usize>(
+    input: Input,
+) -> Result<(Vec<AnalysisExecutor<N>>, Vec<Card>), Error> {
     let max_card_count = N;
     let max_card_cost = MAX_COST as usize;
     let Input {
@@ -107,7 +162,8 @@ pub fn parse<const N: usize>(input: Input) -> Result<Vec<AnalysisExecutor<N>>, E
         .into_iter()
         .map(|a| map_analysis::<N>(a, &name_to_id, &named_conditions, parsed_cost_profile))
         .collect::<Result<Vec<_>, _>>()?;
-    Ok(result)
+    let pool = name_to_id.into_values().collect();
+    Ok((result, pool))
 }
 
 fn map_analysis<const N: usize>(
@@ -126,6 +182,7 @@ fn map_analysis<const N: usize>(
             map_custom_analysis(name, conditions, name_to_id, named_conditions)
         }
         ("cost_efficiency", None) => map_cost_efficiency(name, cost_profile),
+        ("optimal_play", None) => map_optimal_play(name, cost_profile),
         _ => Err(Error::Kind),
     }
 }
@@ -137,51 +194,47 @@ fn map_custom_analysis<const N: usize>(
     named_conditions: &HashMap<String, Condition>,
 ) -> Result<AnalysisExecutor<N>, Error> {
     let mut deck = Vec::new();
-    let mut analysis_conditions: Vec<Box<dyn crate::condition::Condition>> = Vec::new();
+    let mut builder = ConditionDagBuilder::new();
+    let mut roots = Vec::new();
+    let mut raw_conditions: Vec<Box<dyn crate::condition::Condition>> = Vec::new();
     for condition in conditions {
-        enrich_condition(
-            name_to_id,
-            named_conditions,
-            &mut analysis_conditions,
-            &mut deck,
-            condition,
-        )?;
-        // match condition {
-        //     Condition::CardCondition(card_condition) => {}
-        //     Condition::AllOfCondition(_) => {}
-        //     Condition::AnyOfCondition(_) => {}
-        //     Condition::ReferenceCondition(_) => {}
-        // }
-        // let Condition {
-        //     card_name,
-        //     comes_at_or_before,
-        // } = condition;
-        // let card_id_and_cost = name_to_id
-        //     .get(&card_name)
-        //     .ok_or(Error::UnknownCardNameAnalysis(card_name.clone()))?;
-        // let card_identity = CardIdentity::Full(*card_id_and_cost);
-        // if !deck.contains(&card_identity) {
-        //     deck.push(card_identity);
-        // }
-        // let c = crate::condition::AllOf::new(vec![
-        //     Box::new(crate::condition::CardIdCondition::new(
-        //         card_id_and_cost.id(),
-        //     )),
-        //     Box::new(crate::condition::ComesAtOrBeforeCondition::new(
-        //         TurnNumber::from(comes_at_or_before),
-        //     )),
-        // ]);
-        // analysis_conditions.push(Box::new(crate::condition::LockConditionResult::new(c)));
+        match condition {
+            Condition::ExpressionCondition(expression) => {
+                raw_conditions.push(Box::new(parse_expression(expression, name_to_id, &mut deck)?));
+            }
+            Condition::NotCondition(_) | Condition::NOfCondition(_) => {
+                raw_conditions.push(compile_raw_condition(
+                    name_to_id,
+                    named_conditions,
+                    &mut deck,
+                    condition,
+                )?);
+            }
+            other => {
+                roots.push(compile_condition(
+                    name_to_id,
+                    named_conditions,
+                    &mut builder,
+                    &mut deck,
+                    other,
+                )?);
+            }
+        }
+    }
+
+    let mut parts: Vec<Box<dyn crate::condition::Condition>> = Vec::new();
+    if !roots.is_empty() {
+        let root = builder.all_of(roots);
+        parts.push(Box::new(DagCondition::new(Arc::new(builder.build()), root)));
     }
+    parts.append(&mut raw_conditions);
+    let condition: Box<dyn crate::condition::Condition> = Box::new(AllOf::new(parts));
 
     for _ in 0..N - deck.len() {
         deck.push(CardIdentity::None);
     }
 
-    let analysis = crate::condition_count::ConditionCount::new(
-        name,
-        crate::condition::AllOf::new(analysis_conditions),
-    );
+    let analysis = crate::condition_count::ConditionCount::new(name, condition);
 
     Ok(AnalysisExecutor::new(
         Deck::<CardIdentity, N>::from(&deck[..]),
@@ -190,13 +243,36 @@ fn map_custom_analysis<const N: usize>(
     ))
 }
 
-fn enrich_condition(
+fn parse_expression(
+    expression: ExpressionCondition,
+    name_to_id: &HashMap<String, Card>,
+    deck: &mut Vec<CardIdentity>,
+) -> Result<Program, Error> {
+    let ExpressionCondition { expression, cards } = expression;
+    for card_name in cards {
+        let card = name_to_id
+            .get(&card_name)
+            .ok_or(Error::UnknownCardNameAnalysis(card_name))?;
+        let card_identity = CardIdentity::Full(*card);
+        if !deck.contains(&card_identity) {
+            deck.push(card_identity);
+        }
+    }
+    Program::parse(&expression).map_err(|err| Error::InvalidExpression(expression, err))
+}
+
+/// Compiles one `Condition` (resolving `ReferenceCondition`s inline) into `builder`, returning
+/// the `NodeId` it was compiled to. Because `ConditionDagBuilder` interns structurally-equal
+/// nodes, a condition reached more than once (e.g. the same named reference used twice, or
+/// shared by sibling top-level conditions of the same analysis) collapses onto one node
+/// instead of being re-added as a duplicate subtree.
+fn compile_condition(
     name_to_id: &HashMap<String, Card>,
     named_conditions: &HashMap<String, Condition>,
-    conditions: &mut Vec<Box<dyn crate::condition::Condition>>,
+    builder: &mut ConditionDagBuilder,
     deck: &mut Vec<CardIdentity>,
     condition: Condition,
-) -> Result<(), Error> {
+) -> Result<NodeId, Error> {
     match condition {
         Condition::CardCondition(card_condition) => {
             let CardCondition {
@@ -210,47 +286,43 @@ fn enrich_condition(
             if !deck.contains(&card_identity) {
                 deck.push(card_identity);
             }
-            let c = crate::condition::AllOf::new(vec![
-                Box::new(crate::condition::CardIdCondition::new(
-                    card_id_and_cost.id(),
-                )),
-                Box::new(crate::condition::ComesAtOrBeforeCondition::new(
-                    TurnNumber::from(comes_at_or_before),
-                )),
-            ]);
-            conditions.push(Box::new(crate::condition::LockConditionResult::new(c)));
+            let card_node = builder.card(card_id_and_cost.id());
+            let before_node = builder.drawn_at_or_before(TurnNumber::from(comes_at_or_before));
+            let leaf = builder.all_of(vec![card_node, before_node]);
+            // Latch this per-card fact across the whole deck before any sibling `AllOf`/`AnyOf`
+            // combines it: `card_node`/`before_node` each only hold at one instant, so without
+            // locking, two distinct cards' facts would always intersect to nothing even when
+            // both are eventually satisfied. Mirrors `dsl`'s implicit lock after `card before
+            // turn` atoms (see `condition_dag::NodeSpec::Lock`).
+            Ok(builder.lock(leaf))
         }
         Condition::AllOfCondition(all_of_condition) => {
             let AllOfCondition { all_of } = all_of_condition;
-            let mut child_conditions: Vec<Box<dyn crate::condition::Condition>> = Vec::new();
+            let mut children = Vec::new();
             for child_condition in all_of {
-                enrich_condition(
+                children.push(compile_condition(
                     name_to_id,
                     named_conditions,
-                    &mut child_conditions,
+                    builder,
                     deck,
                     child_condition,
-                )?;
+                )?);
             }
-            conditions.push(Box::new(LockConditionResult::new(AllOf::new(
-                child_conditions,
-            ))));
+            Ok(builder.all_of(children))
         }
         Condition::AnyOfCondition(any_of_condition) => {
             let AnyOfCondition { any_of } = any_of_condition;
-            let mut child_conditions: Vec<Box<dyn crate::condition::Condition>> = Vec::new();
+            let mut children = Vec::new();
             for child_condition in any_of {
-                enrich_condition(
+                children.push(compile_condition(
                     name_to_id,
                     named_conditions,
-                    &mut child_conditions,
+                    builder,
                     deck,
                     child_condition,
-                )?;
+                )?);
             }
-            conditions.push(Box::new(LockConditionResult::new(AnyOf::new(
-                child_conditions,
-            ))));
+            Ok(builder.any_of(children))
         }
         Condition::ReferenceCondition(reference_condition) => {
             let ReferenceCondition { reference } = reference_condition;
@@ -258,16 +330,87 @@ fn enrich_condition(
                 .get(&reference)
                 .cloned()
                 .ok_or(Error::UnknownConditionReference(reference))?;
-            enrich_condition(
+            compile_condition(
                 name_to_id,
                 named_conditions,
-                conditions,
+                builder,
                 deck,
                 referenced_condition,
-            )?;
+            )
+        }
+        Condition::ExpressionCondition(_) => Err(Error::ExpressionConditionNotTopLevel),
+        Condition::NotCondition(_) | Condition::NOfCondition(_) => {
+            Err(Error::NotOrNOfConditionNotTopLevel)
+        }
+    }
+}
+
+/// Compiles one `Condition` straight to a `Box<dyn Condition>` tree instead of into the shared
+/// `ConditionDagBuilder`, the way `parse_expression` does for `ExpressionCondition`: used for
+/// `NotCondition`/`NOfCondition`, which the DAG has no node for, and recursively for whatever
+/// they themselves contain, so e.g. a `not_condition` wrapping an `all_of_condition` still
+/// works even though that `all_of_condition` never touches the DAG either.
+fn compile_raw_condition(
+    name_to_id: &HashMap<String, Card>,
+    named_conditions: &HashMap<String, Condition>,
+    deck: &mut Vec<CardIdentity>,
+    condition: Condition,
+) -> Result<Box<dyn crate::condition::Condition>, Error> {
+    match condition {
+        Condition::CardCondition(card_condition) => {
+            let CardCondition {
+                card_name,
+                comes_at_or_before,
+            } = card_condition;
+            let card_id_and_cost = name_to_id
+                .get(&card_name)
+                .ok_or(Error::UnknownCardNameAnalysis(card_name.clone()))?;
+            let card_identity = CardIdentity::Full(*card_id_and_cost);
+            if !deck.contains(&card_identity) {
+                deck.push(card_identity);
+            }
+            Ok(Box::new(AllOf::new(vec![
+                Box::new(crate::condition::CardIdCondition::new(
+                    card_id_and_cost.id(),
+                )),
+                Box::new(crate::condition::ComesAtOrBeforeCondition::new(
+                    TurnNumber::from(comes_at_or_before),
+                )),
+            ])))
+        }
+        Condition::AllOfCondition(AllOfCondition { all_of }) => {
+            let children = all_of
+                .into_iter()
+                .map(|child| compile_raw_condition(name_to_id, named_conditions, deck, child))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Box::new(AllOf::new(children)))
+        }
+        Condition::AnyOfCondition(AnyOfCondition { any_of }) => {
+            let children = any_of
+                .into_iter()
+                .map(|child| compile_raw_condition(name_to_id, named_conditions, deck, child))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Box::new(crate::condition::AnyOf::new(children)))
+        }
+        Condition::ReferenceCondition(ReferenceCondition { reference }) => {
+            let referenced_condition = named_conditions
+                .get(&reference)
+                .cloned()
+                .ok_or(Error::UnknownConditionReference(reference))?;
+            compile_raw_condition(name_to_id, named_conditions, deck, referenced_condition)
         }
+        Condition::NotCondition(NotCondition { not }) => Ok(Box::new(crate::condition::Not::new(
+            compile_raw_condition(name_to_id, named_conditions, deck, *not)?,
+        ))),
+        Condition::NOfCondition(NOfCondition { n_of, conditions }) => {
+            let children = conditions
+                .into_iter()
+                .map(|child| compile_raw_condition(name_to_id, named_conditions, deck, child))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Box::new(crate::condition::NOf::new(n_of, children)))
+        }
+        Condition::ExpressionCondition(_) => Err(Error::ExpressionConditionNotTopLevel),
     }
-    Ok(())
 }
 
 fn extract_condition_references(
@@ -304,6 +447,27 @@ fn map_cost_efficiency<const N: usize>(
     ))
 }
 
+fn map_optimal_play<const N: usize>(
+    name: String,
+    cost_profile: [u8; { MAX_COST + 1 } as usize],
+) -> Result<AnalysisExecutor<N>, Error> {
+    let mut deck = Vec::new();
+
+    for (cost, amount) in cost_profile.into_iter().enumerate() {
+        for _ in 0..amount {
+            deck.push(CardIdentity::Cost(Energy::from(cost as u8)));
+        }
+    }
+
+    let analysis = OptimalPlayAnalysis::<{ (MAX_COST + 1) as usize }>::new(name);
+
+    Ok(AnalysisExecutor::new(
+        Deck::<CardIdentity, N>::from(&deck[..]),
+        standard_turn_profile(),
+        vec![Box::new(analysis)],
+    ))
+}
+
 fn associate_card_name_with_card_id(
     cards: Vec<CardName>,
     mut cost_profile: [u8; { MAX_COST + 1 } as usize],
@@ -370,6 +534,208 @@ impl std::fmt::Display for Error {
                 "Condition reference \"{}\" in analysis is unknown",
                 reference_name
             ),
+            Error::InvalidExpression(expression, err) => write!(
+                f,
+                "Condition expression \"{}\" failed to parse: {}",
+                expression, err
+            ),
+            Error::ExpressionConditionNotTopLevel => write!(
+                f,
+                "Expression conditions can only be used as top-level entries in an analysis's \
+                 condition list, not nested inside all_of/any_of/reference/not/n_of"
+            ),
+            Error::NotOrNOfConditionNotTopLevel => write!(
+                f,
+                "not_condition/n_of_condition can only be used as top-level entries in an \
+                 analysis's condition list, or nested inside another not_condition/n_of_condition, \
+                 not inside all_of/any_of/reference"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(analysis: Analysis) -> Input {
+        Input {
+            cost_profile: vec![0, 2, 0, 0, 0, 0, 0],
+            cards: vec![
+                CardName {
+                    name: "Ace".to_string(),
+                    cost: 1,
+                },
+                CardName {
+                    name: "King".to_string(),
+                    cost: 1,
+                },
+            ],
+            condition_references: Vec::new(),
+            analysis: vec![analysis],
         }
     }
+
+    #[test]
+    fn parses_a_custom_analysis_driven_by_an_expression_condition() {
+        let analysis = Analysis {
+            kind: "custom".to_string(),
+            name: "combo".to_string(),
+            conditions: Some(vec![Condition::ExpressionCondition(ExpressionCondition {
+                expression: "card 0 before turn 1 and card 1 before turn 2".to_string(),
+                cards: vec!["Ace".to_string(), "King".to_string()],
+            })]),
+        };
+
+        let result = parse::<2>(input(analysis));
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn rejects_an_expression_that_references_an_unknown_card() {
+        let analysis = Analysis {
+            kind: "custom".to_string(),
+            name: "combo".to_string(),
+            conditions: Some(vec![Condition::ExpressionCondition(ExpressionCondition {
+                expression: "card 0 before turn 1".to_string(),
+                cards: vec!["Queen".to_string()],
+            })]),
+        };
+
+        let err = parse::<2>(input(analysis)).err().unwrap();
+
+        assert!(matches!(err, Error::UnknownCardNameAnalysis(name) if name == "Queen"));
+    }
+
+    #[test]
+    fn rejects_an_expression_condition_nested_inside_all_of() {
+        let analysis = Analysis {
+            kind: "custom".to_string(),
+            name: "combo".to_string(),
+            conditions: Some(vec![Condition::AllOfCondition(AllOfCondition {
+                all_of: vec![Condition::ExpressionCondition(ExpressionCondition {
+                    expression: "card 0 before turn 1".to_string(),
+                    cards: vec!["Ace".to_string()],
+                })],
+            })]),
+        };
+
+        let err = parse::<2>(input(analysis)).err().unwrap();
+
+        assert!(matches!(err, Error::ExpressionConditionNotTopLevel));
+    }
+
+    #[test]
+    fn parses_a_custom_analysis_driven_by_a_not_condition() {
+        let analysis = Analysis {
+            kind: "custom".to_string(),
+            name: "combo".to_string(),
+            conditions: Some(vec![Condition::NotCondition(NotCondition {
+                not: Box::new(Condition::CardCondition(CardCondition {
+                    card_name: "Ace".to_string(),
+                    comes_at_or_before: 1,
+                })),
+            })]),
+        };
+
+        let result = parse::<2>(input(analysis));
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn parses_a_custom_analysis_driven_by_an_n_of_condition() {
+        let analysis = Analysis {
+            kind: "custom".to_string(),
+            name: "combo".to_string(),
+            conditions: Some(vec![Condition::NOfCondition(NOfCondition {
+                n_of: 1,
+                conditions: vec![
+                    Condition::CardCondition(CardCondition {
+                        card_name: "Ace".to_string(),
+                        comes_at_or_before: 0,
+                    }),
+                    Condition::CardCondition(CardCondition {
+                        card_name: "King".to_string(),
+                        comes_at_or_before: 0,
+                    }),
+                ],
+            })]),
+        };
+
+        let result = parse::<2>(input(analysis));
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn rejects_a_not_condition_nested_inside_all_of() {
+        let analysis = Analysis {
+            kind: "custom".to_string(),
+            name: "combo".to_string(),
+            conditions: Some(vec![Condition::AllOfCondition(AllOfCondition {
+                all_of: vec![Condition::NotCondition(NotCondition {
+                    not: Box::new(Condition::CardCondition(CardCondition {
+                        card_name: "Ace".to_string(),
+                        comes_at_or_before: 0,
+                    })),
+                })],
+            })]),
+        };
+
+        let err = parse::<2>(input(analysis)).err().unwrap();
+
+        assert!(matches!(err, Error::NotOrNOfConditionNotTopLevel));
+    }
+
+    #[test]
+    fn an_all_of_over_two_distinct_cards_counts_decks_where_both_are_drawn() {
+        let analysis = Analysis {
+            kind: "custom".to_string(),
+            name: "combo".to_string(),
+            conditions: Some(vec![Condition::AllOfCondition(AllOfCondition {
+                all_of: vec![
+                    Condition::CardCondition(CardCondition {
+                        card_name: "Ace".to_string(),
+                        comes_at_or_before: 1,
+                    }),
+                    Condition::CardCondition(CardCondition {
+                        card_name: "King".to_string(),
+                        comes_at_or_before: 1,
+                    }),
+                ],
+            })]),
+        };
+
+        let executors = parse::<2>(input(analysis)).unwrap();
+        let results = executors
+            .into_iter()
+            .next()
+            .unwrap()
+            .execute::<crate::permutation_simple::AllPermutationsIterator<CardIdentity>>(
+                crate::analyse::SuppressWarnings::No,
+            )
+            .unwrap();
+        let result_map = results[0].as_map();
+
+        assert_eq!(result_map.get("count").map(String::as_str), Some("2"));
+        assert_eq!(result_map.get("total_amount").map(String::as_str), Some("2"));
+    }
+
+    #[test]
+    fn rejects_an_invalid_expression() {
+        let analysis = Analysis {
+            kind: "custom".to_string(),
+            name: "combo".to_string(),
+            conditions: Some(vec![Condition::ExpressionCondition(ExpressionCondition {
+                expression: "card 0 or or".to_string(),
+                cards: vec!["Ace".to_string()],
+            })]),
+        };
+
+        let err = parse::<2>(input(analysis)).err().unwrap();
+
+        assert!(matches!(err, Error::InvalidExpression(_, _)));
+    }
 }
@@ -1,16 +1,25 @@
-use crate::analyse::{AnalysisExecutor, SuppressWarnings};
+use crate::analyse::{AnalysisExecutor, AnalysisResult, SuppressWarnings};
 use crate::deck::{CardIdentity, Deck};
 use analyse::PermutationIterator;
 
 mod analyse;
+mod beam_search;
+mod bitset;
 mod condition;
 mod condition_count;
+mod condition_dag;
 mod cost_efficiency;
 mod deck;
+mod dsl;
 mod input;
+mod optimal_play;
+mod optimize;
 mod permutation_optimized;
+mod permutation_sampled;
 mod permutation_simple;
 mod placement;
+mod repl;
+mod streaming;
 #[cfg(test)]
 mod tests;
 
@@ -27,16 +36,36 @@ fn main() {
         }
     };
     let input = input::read_from_file(&filename);
-    let analysis = match input::parse::<{ MAX_ID as usize }>(input) {
+    let cost_profile = to_cost_profile_array(input.cost_profile.clone());
+    let (analysis, pool) = match input::parse_with_pool::<{ MAX_ID as usize }>(input) {
         Ok(result) => result,
         Err(err) => {
             println!("ERROR: {}", err);
             std::process::exit(1);
         }
     };
+    let output_format = OutputFormat::from_args();
     match std::env::args().nth(2) {
         Some(simple) if simple.as_str() == "simple" => {
-            execute::<crate::permutation_simple::AllPermutationsIterator<CardIdentity>>(analysis);
+            execute::<crate::permutation_simple::AllPermutationsIterator<CardIdentity>>(
+                analysis,
+                output_format,
+            );
+        }
+        Some(opt) if opt.as_str() == "optimize" => {
+            run_optimize(analysis, pool);
+        }
+        Some(repl) if repl.as_str() == "repl" => {
+            repl::run::<{ MAX_ID as usize }>(analysis, pool);
+        }
+        Some(parallel) if parallel.as_str() == "parallel" => {
+            run_parallel(analysis, output_format);
+        }
+        Some(beam) if beam.as_str() == "beam" => {
+            run_beam_search(pool, cost_profile);
+        }
+        Some(histogram) if histogram.as_str() == "histogram" => {
+            run_histogram(analysis);
         }
         _ => {
             execute::<
@@ -44,15 +73,178 @@ fn main() {
                     CardIdentity,
                     { MAX_ID as usize },
                 >,
-            >(analysis);
+            >(analysis, output_format);
+        }
+    }
+}
+
+/// Output mode for [`execute`], selected by passing `--json` or `--ndjson` anywhere on the
+/// command line: `Text` prints each result's `Display` form (the default), `Json` prints a
+/// single JSON array of every result's [`AnalysisResult::as_json`], and `Ndjson` prints one
+/// such JSON object per line for streaming into other tools.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+    Ndjson,
+}
+
+impl OutputFormat {
+    fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        if args.iter().any(|arg| arg == "--ndjson") {
+            OutputFormat::Ndjson
+        } else if args.iter().any(|arg| arg == "--json") {
+            OutputFormat::Json
+        } else {
+            OutputFormat::Text
         }
     }
 }
 
+/// `optimize` subcommand: searches for the `N`-card deck, drawn from the input's named card
+/// pool, that best optimizes one field of one of the input's already-configured analyses via
+/// simulated annealing. Fourth CLI arg selects the analysis by name, fifth the result field to
+/// optimize, and an optional sixth arg of `min` switches from maximizing (the default) to
+/// minimizing it.
+fn run_optimize(
+    analysis: Vec<AnalysisExecutor<{ MAX_ID as usize }>>,
+    pool: Vec<crate::deck::Card>,
+) {
+    let name = match std::env::args().nth(3) {
+        Some(name) => name,
+        None => {
+            println!("Please specify which analysis to optimize by name (4th argument)");
+            std::process::exit(1);
+        }
+    };
+    let field = match std::env::args().nth(4) {
+        Some(field) => field,
+        None => {
+            println!("Please specify which result field to optimize (5th argument)");
+            std::process::exit(1);
+        }
+    };
+    let objective = match std::env::args().nth(5).as_deref() {
+        Some("min") => optimize::Objective::Minimize,
+        _ => optimize::Objective::Maximize,
+    };
+
+    let template = match analysis.into_iter().find(|a| a.name() == name) {
+        Some(template) => template,
+        None => {
+            println!("ERROR: no analysis named \"{}\"", name);
+            std::process::exit(1);
+        }
+    };
+
+    let config = optimize::OptimizeConfig {
+        time_limit: std::time::Duration::from_secs(10),
+        initial_temperature: 1.0,
+        final_temperature: 0.01,
+        sample_size: 10_000,
+        seed: 0x2545_F491_4F6C_DD1D,
+    };
+
+    let best = optimize::search(&pool, |deck| template.retarget(deck), &field, objective, config);
+
+    println!("{}", best);
+}
+
+fn to_cost_profile_array(cost_profile: Vec<u8>) -> [u8; { MAX_COST + 1 } as usize] {
+    let mut array = [0u8; { MAX_COST + 1 } as usize];
+    for (i, amount) in cost_profile.into_iter().enumerate() {
+        array[i] = amount;
+    }
+    array
+}
+
+/// `beam` subcommand: searches for the `N`-card deck, drawn from the input's named card pool,
+/// that best satisfies a DSL objective via `beam_search::search`. Fourth CLI arg is the objective
+/// as a `dsl::Program` expression (e.g. `"card 0 before turn 1"`), addressing cards by the same
+/// declaration-order `Id` the input's top-level `cards` list assigns them.
+fn run_beam_search(pool: Vec<crate::deck::Card>, cost_profile: [u8; { MAX_COST + 1 } as usize]) {
+    let expression = match std::env::args().nth(3) {
+        Some(expression) => expression,
+        None => {
+            println!("Please specify the objective as a DSL expression (4th argument)");
+            std::process::exit(1);
+        }
+    };
+    let objective: Box<dyn crate::condition::Condition> = match dsl::Program::parse(&expression) {
+        Ok(program) => Box::new(program),
+        Err(err) => {
+            println!("ERROR: failed to parse objective expression: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let config = beam_search::BeamSearchConfig {
+        beam_width: 50,
+        depth: MAX_ID as usize,
+        sample_size: 10_000,
+        seed: 0x2545_F491_4F6C_DD1D,
+    };
+
+    let best = beam_search::search::<{ MAX_ID as usize }>(&pool, cost_profile, objective, config);
+
+    println!("{}", best);
+}
+
+/// `histogram` subcommand: streams every permutation of an already-configured analysis's deck
+/// through `streaming::ExternalSortExecutor` to report the turn-by-turn distribution of when a
+/// DSL objective first becomes satisfied, rather than just the fraction of permutations that
+/// ever satisfy it the way the analysis's own `ConditionCount`-based result would. Fourth CLI arg
+/// selects the analysis by name (for its deck and turn profile), fifth is the objective as a
+/// `dsl::Program` expression.
+fn run_histogram(analysis: Vec<AnalysisExecutor<{ MAX_ID as usize }>>) {
+    let name = match std::env::args().nth(3) {
+        Some(name) => name,
+        None => {
+            println!("Please specify which analysis's deck to use by name (4th argument)");
+            std::process::exit(1);
+        }
+    };
+    let expression = match std::env::args().nth(4) {
+        Some(expression) => expression,
+        None => {
+            println!("Please specify the objective as a DSL expression (5th argument)");
+            std::process::exit(1);
+        }
+    };
+
+    let template = match analysis.into_iter().find(|a| a.name() == name) {
+        Some(template) => template,
+        None => {
+            println!("ERROR: no analysis named \"{}\"", name);
+            std::process::exit(1);
+        }
+    };
+    let objective = match dsl::Program::parse(&expression) {
+        Ok(program) => program,
+        Err(err) => {
+            println!("ERROR: failed to parse objective expression: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let executor = streaming::ExternalSortExecutor::new(
+        template.deck(),
+        template.turn_profile(),
+        Box::new(streaming::SatisfiedTurnAnalysis::new(name, objective)),
+    );
+
+    let result = executor
+        .execute::<crate::permutation_optimized::DeckPermutationIterator<CardIdentity, { MAX_ID as usize }>>();
+
+    println!("{}", result);
+}
+
 fn execute<T: PermutationIterator<Deck<CardIdentity, { MAX_ID as usize }>>>(
     analysis: Vec<AnalysisExecutor<{ MAX_ID as usize }>>,
+    output_format: OutputFormat,
 ) {
-    for result in analysis
+    let results = analysis
         .into_iter()
         .map(|a| a.execute::<T>(SuppressWarnings::No))
         .flat_map(|result| match result {
@@ -61,8 +253,55 @@ fn execute<T: PermutationIterator<Deck<CardIdentity, { MAX_ID as usize }>>>(
                 println!("WARNING: {}", err);
                 analysis.execute::<T>(SuppressWarnings::Yes).unwrap()
             }
-        })
-    {
-        println!("{}", result);
+        });
+
+    print_results(results, output_format);
+}
+
+/// `parallel` subcommand: same exhaustive analysis as the `simple` subcommand (all `N!`
+/// orderings, duplicates of repeated cards included, not the default path's deduplicated
+/// ones), but split across one worker thread per available core via
+/// `AnalysisExecutor::execute_parallel` and merged back together, rather than walking every
+/// permutation on a single thread.
+fn run_parallel(analysis: Vec<AnalysisExecutor<{ MAX_ID as usize }>>, output_format: OutputFormat) {
+    let worker_count = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+
+    let results = analysis
+        .into_iter()
+        .map(|a| a.execute_parallel(worker_count, SuppressWarnings::No))
+        .flat_map(|result| match result {
+            Ok(success) => success,
+            Err((analysis, err)) => {
+                println!("WARNING: {}", err);
+                analysis
+                    .execute_parallel(worker_count, SuppressWarnings::Yes)
+                    .unwrap()
+            }
+        });
+
+    print_results(results, output_format);
+}
+
+fn print_results(
+    results: impl Iterator<Item = Box<dyn AnalysisResult>>,
+    output_format: OutputFormat,
+) {
+    match output_format {
+        OutputFormat::Text => {
+            for result in results {
+                println!("{}", result);
+            }
+        }
+        OutputFormat::Json => {
+            let values: Vec<serde_json::Value> = results.map(|r| r.as_json()).collect();
+            println!("{}", serde_json::Value::Array(values));
+        }
+        OutputFormat::Ndjson => {
+            for result in results {
+                println!("{}", result.as_json());
+            }
+        }
     }
 }
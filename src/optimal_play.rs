@@ -0,0 +1,241 @@
+use crate::analyse::{Analysis, AnalysisResult};
+use crate::deck::{CardIdentity, Energy, EnergyProfile, Turn, TurnNumber};
+use std::collections::HashMap;
+
+/// Models the real decision of which subset of drawn-but-unplayed cards to play each turn,
+/// rather than assuming a fixed play, by solving a bounded subset-sum DP over the turn's
+/// energy budget. Reports total wasted energy (budget left unspent because no affordable
+/// subset used it) and a turn-by-turn histogram, a more realistic mana-curve quality metric
+/// than `CostEfficiencyAnalysis`'s running total.
+#[derive(Debug)]
+pub struct OptimalPlayAnalysis<const M: usize> {
+    name: String,
+    hand: EnergyProfile<M>,
+    current_turn: Option<Turn>,
+    total_wasted: u64,
+    wasted_by_turn: HashMap<TurnNumber, u64>,
+    number_of_decks: u64,
+}
+
+#[derive(Debug)]
+struct OptimalPlayAnalysisResult {
+    name: String,
+    total_wasted: u64,
+    number_of_decks: u64,
+    wasted_by_turn: HashMap<TurnNumber, u64>,
+}
+
+impl<const M: usize> OptimalPlayAnalysis<M> {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            hand: EnergyProfile::default(),
+            current_turn: None,
+            total_wasted: 0,
+            wasted_by_turn: HashMap::new(),
+            number_of_decks: 0,
+        }
+    }
+
+    fn accept(&mut self, cost: Energy, turn: Turn) {
+        if let Some(current) = self.current_turn {
+            if current.number != turn.number {
+                self.resolve_turn(current);
+            }
+        }
+        self.current_turn = Some(turn);
+        self.hand[cost] += 1;
+    }
+
+    fn next_deck(&mut self) {
+        if let Some(current) = self.current_turn.take() {
+            self.resolve_turn(current);
+        }
+        self.number_of_decks += 1;
+        self.hand = EnergyProfile::default();
+    }
+
+    /// Bounded subset-sum DP: `reachable[e]` is true if some subset of the hand sums to
+    /// exactly `e`; `chosen_cost[e]` records the last item added to reach it, so the optimal
+    /// subset can be walked back and removed from the hand afterwards.
+    fn resolve_turn(&mut self, turn: Turn) {
+        let cap = turn.energy.value() as usize;
+        let mut reachable = vec![false; cap + 1];
+        let mut chosen_cost = vec![None; cap + 1];
+        reachable[0] = true;
+
+        for cost in 1..=cap.min(u8::MAX as usize) as u8 {
+            let count = self.hand[Energy::from(cost)];
+            for _ in 0..count {
+                for e in (cost as usize..=cap).rev() {
+                    if reachable[e - cost as usize] && !reachable[e] {
+                        reachable[e] = true;
+                        chosen_cost[e] = Some(cost);
+                    }
+                }
+            }
+        }
+
+        let best_spent = (0..=cap).rev().find(|e| reachable[*e]).unwrap_or(0);
+        let mut remaining = best_spent;
+        while remaining > 0 {
+            let cost = chosen_cost[remaining].expect("reachable sums are built from chosen costs");
+            self.hand[Energy::from(cost)] -= 1;
+            remaining -= cost as usize;
+        }
+
+        let wasted = (cap - best_spent) as u64;
+        self.total_wasted += wasted;
+        *self.wasted_by_turn.entry(turn.number).or_insert(0) += wasted;
+    }
+}
+
+impl<const M: usize> Analysis for OptimalPlayAnalysis<M> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn accept(&mut self, card: CardIdentity, turn: Turn) {
+        if let CardIdentity::Cost(cost) = card {
+            self.accept(cost, turn);
+        } else {
+            panic!(
+                "{} only accepts cost card identities",
+                stringify!(OptimalPlayAnalysis)
+            );
+        }
+    }
+
+    fn next_deck(&mut self) {
+        self.next_deck();
+    }
+
+    fn result(&self) -> Box<dyn AnalysisResult> {
+        Box::new(OptimalPlayAnalysisResult {
+            name: self.name.clone(),
+            total_wasted: self.total_wasted,
+            number_of_decks: self.number_of_decks,
+            wasted_by_turn: self.wasted_by_turn.clone(),
+        })
+    }
+
+    fn split(&self) -> Box<dyn Analysis> {
+        Box::new(Self::new(self.name.clone()))
+    }
+
+    fn merge(&mut self, other: Box<dyn Analysis>) {
+        let other = other
+            .as_any()
+            .downcast_ref::<Self>()
+            .expect("merge only ever receives a Box produced by this analysis's own split");
+        self.total_wasted += other.total_wasted;
+        self.number_of_decks += other.number_of_decks;
+        for (turn, wasted) in other.wasted_by_turn.iter() {
+            *self.wasted_by_turn.entry(*turn).or_insert(0) += wasted;
+        }
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl AnalysisResult for OptimalPlayAnalysisResult {
+    fn as_map(&self) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        map.insert("name".to_owned(), self.name.clone());
+        map.insert("total_wasted".to_owned(), format!("{}", self.total_wasted));
+        map.insert(
+            "number_of_decks".to_owned(),
+            format!("{}", self.number_of_decks),
+        );
+        map.insert("wasted_by_turn".to_owned(), self.histogram());
+        map
+    }
+}
+
+impl OptimalPlayAnalysisResult {
+    fn histogram(&self) -> String {
+        let mut turns: Vec<_> = self.wasted_by_turn.keys().copied().collect();
+        turns.sort();
+        turns
+            .into_iter()
+            .map(|turn| format!("{}:{}", turn.value(), self.wasted_by_turn[&turn]))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl std::fmt::Display for OptimalPlayAnalysisResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: average wasted energy per deck: {:.2} ({} decks analysed, by turn: {})",
+            self.name,
+            self.total_wasted as f64 / self.number_of_decks as f64,
+            self.number_of_decks,
+            self.histogram()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OptimalPlayAnalysis;
+    use crate::deck::{Energy, Turn, TurnNumber};
+
+    fn turn(number: u8, energy: u8) -> Turn {
+        Turn {
+            number: TurnNumber::from(number),
+            energy: Energy::from(energy),
+        }
+    }
+
+    #[test]
+    fn spends_the_exact_fit_with_no_waste() {
+        let mut analysis = OptimalPlayAnalysis::<6>::new("optimal play".to_owned());
+        analysis.accept(Energy::from(2), turn(1, 2));
+        analysis.next_deck();
+
+        assert_eq!(analysis.total_wasted, 0);
+    }
+
+    #[test]
+    fn picks_the_affordable_subset_that_wastes_least() {
+        let mut analysis = OptimalPlayAnalysis::<6>::new("optimal play".to_owned());
+        // Budget 5 with hand {4, 3, 3}: no combination reaches 5 exactly (3+3 is over budget,
+        // 4 alone wastes 1, 3 alone wastes 2), so the optimum spends 4 and wastes 1.
+        analysis.accept(Energy::from(4), turn(1, 5));
+        analysis.accept(Energy::from(3), turn(1, 5));
+        analysis.accept(Energy::from(3), turn(1, 5));
+        analysis.next_deck();
+
+        assert_eq!(analysis.total_wasted, 1);
+    }
+
+    #[test]
+    fn carries_unplayed_cards_into_the_next_turn() {
+        let mut analysis = OptimalPlayAnalysis::<6>::new("optimal play".to_owned());
+        analysis.accept(Energy::from(5), turn(1, 1));
+        analysis.accept(Energy::from(1), turn(2, 2));
+        analysis.next_deck();
+
+        // The cost-1 card is drawn on turn 2, so it isn't in hand yet when turn 1 resolves.
+        // Turn 1: budget 1, hand {5} -> nothing affordable, wastes 1.
+        // Turn 2: budget 2, hand {5, 1} -> best affordable subset is {1}, wastes 1.
+        assert_eq!(analysis.total_wasted, 2);
+    }
+
+    #[test]
+    fn aggregates_wasted_energy_per_turn_across_decks() {
+        let mut analysis = OptimalPlayAnalysis::<6>::new("optimal play".to_owned());
+        analysis.accept(Energy::from(3), turn(1, 2));
+        analysis.next_deck();
+        analysis.accept(Energy::from(1), turn(1, 2));
+        analysis.next_deck();
+
+        assert_eq!(analysis.total_wasted, 3);
+        assert_eq!(analysis.wasted_by_turn.get(&TurnNumber::from(1)), Some(&3));
+        assert_eq!(analysis.number_of_decks, 2);
+    }
+}
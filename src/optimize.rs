@@ -0,0 +1,170 @@
+use crate::analyse::AnalysisExecutor;
+use crate::deck::{Card, CardIdentity, Deck};
+use crate::permutation_sampled::Xorshift64;
+use std::time::{Duration, Instant};
+
+/// Which direction makes a candidate deck better: the score read off the objective field should
+/// be pushed up (`Maximize`, e.g. `ConditionCount`'s `count`) or down (`Minimize`).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Objective {
+    Maximize,
+    Minimize,
+}
+
+/// Tuning knobs for [`search`]: how long to anneal for, the temperature schedule's start and
+/// end points, and how the scoring oracle samples the permutation space for each candidate deck.
+#[derive(Debug, Clone, Copy)]
+pub struct OptimizeConfig {
+    pub time_limit: Duration,
+    pub initial_temperature: f64,
+    pub final_temperature: f64,
+    pub sample_size: u64,
+    pub seed: u64,
+}
+
+/// Searches the space of `N`-card decks drawn from `pool` for the composition that best
+/// optimizes `field` (read off `AnalysisResult::as_map()`) in the direction `objective` asks
+/// for, via simulated annealing: starting from a random deck, each step proposes a neighbor by
+/// swapping one of its cards for a different one from `pool`, scores it by running
+/// `make_analysis` through `AnalysisExecutor::execute_sampled`, and always accepts an
+/// improving move while accepting a worsening move with probability `exp(delta / temperature)`.
+/// The temperature cools geometrically from `initial_temperature` to `final_temperature` over
+/// `time_limit`, sampled against a monotonic clock; the best deck seen at any point is returned,
+/// not just whatever the walk ends on.
+pub fn search<const N: usize>(
+    pool: &[Card],
+    make_analysis: impl Fn(Deck<CardIdentity, N>) -> AnalysisExecutor<N>,
+    field: &str,
+    objective: Objective,
+    config: OptimizeConfig,
+) -> Deck<CardIdentity, N> {
+    let mut rng = Xorshift64::new(config.seed);
+    let mut current = random_deck::<N>(pool, &mut rng);
+    let mut current_score = score(current, &make_analysis, field, &config);
+    let mut best = current;
+    let mut best_score = current_score;
+
+    let start = Instant::now();
+    loop {
+        let elapsed = start.elapsed();
+        if elapsed >= config.time_limit {
+            break;
+        }
+        let progress = elapsed.as_secs_f64() / config.time_limit.as_secs_f64();
+        let temperature = config.initial_temperature
+            * (config.final_temperature / config.initial_temperature).powf(progress);
+
+        let candidate = propose_neighbor(current, pool, &mut rng);
+        let candidate_score = score(candidate, &make_analysis, field, &config);
+        let delta = match objective {
+            Objective::Maximize => candidate_score - current_score,
+            Objective::Minimize => current_score - candidate_score,
+        };
+
+        if delta >= 0.0 || uniform(&mut rng) < (delta / temperature).exp() {
+            current = candidate;
+            current_score = candidate_score;
+
+            let improved = match objective {
+                Objective::Maximize => current_score > best_score,
+                Objective::Minimize => current_score < best_score,
+            };
+            if improved {
+                best = current;
+                best_score = current_score;
+            }
+        }
+    }
+
+    best
+}
+
+fn score<const N: usize>(
+    deck: Deck<CardIdentity, N>,
+    make_analysis: &impl Fn(Deck<CardIdentity, N>) -> AnalysisExecutor<N>,
+    field: &str,
+    config: &OptimizeConfig,
+) -> f64 {
+    make_analysis(deck)
+        .execute_sampled(config.seed, config.sample_size)
+        .pop()
+        .and_then(|result| result.as_map().get(field).and_then(|s| s.parse().ok()))
+        .unwrap_or(0.0)
+}
+
+fn uniform(rng: &mut Xorshift64) -> f64 {
+    (rng.next_u64() as f64) / (u64::MAX as f64)
+}
+
+fn random_deck<const N: usize>(pool: &[Card], rng: &mut Xorshift64) -> Deck<CardIdentity, N> {
+    let mut indices: Vec<usize> = (0..pool.len()).collect();
+    for i in (1..indices.len()).rev() {
+        let j = rng.below(i + 1);
+        indices.swap(i, j);
+    }
+    let mut cards = [CardIdentity::None; N];
+    for slot in 0..N.min(pool.len()) {
+        cards[slot] = CardIdentity::Full(pool[indices[slot]]);
+    }
+    Deck::from(cards)
+}
+
+fn propose_neighbor<const N: usize>(
+    deck: Deck<CardIdentity, N>,
+    pool: &[Card],
+    rng: &mut Xorshift64,
+) -> Deck<CardIdentity, N> {
+    let mut cards = [CardIdentity::None; N];
+    for (i, card) in deck.card_iter().enumerate() {
+        cards[i] = card;
+    }
+
+    let candidates: Vec<Card> = pool
+        .iter()
+        .copied()
+        .filter(|card| !cards.contains(&CardIdentity::Full(*card)))
+        .collect();
+    if candidates.is_empty() {
+        return deck;
+    }
+
+    let slot = rng.below(N);
+    cards[slot] = CardIdentity::Full(candidates[rng.below(candidates.len())]);
+    Deck::from(cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{search, Objective, OptimizeConfig};
+    use crate::analyse::{standard_turn_profile, AnalysisExecutor};
+    use crate::condition::CardIdCondition;
+    use crate::condition_count::ConditionCount;
+    use crate::deck::{Card, CardIdentity, Deck, Id};
+    use std::time::Duration;
+
+    fn config() -> OptimizeConfig {
+        OptimizeConfig {
+            time_limit: Duration::from_millis(200),
+            initial_temperature: 1.0,
+            final_temperature: 0.01,
+            sample_size: 10_000,
+            seed: 7,
+        }
+    }
+
+    #[test]
+    fn finds_the_pool_card_that_satisfies_the_objective() {
+        let pool = [Card::new(0, 1), Card::new(1, 1), Card::new(2, 1)];
+        let make_analysis = |deck: Deck<CardIdentity, 2>| {
+            let analysis = ConditionCount::new(
+                "objective".to_string(),
+                CardIdCondition::new(Id::from(0)),
+            );
+            AnalysisExecutor::new(deck, standard_turn_profile(), vec![Box::new(analysis)])
+        };
+
+        let deck = search::<2>(&pool, make_analysis, "count", Objective::Maximize, config());
+
+        assert!(deck.card_iter().any(|card| card.id() == Some(Id::from(0))));
+    }
+}
@@ -62,7 +62,7 @@ impl<T: Default + Copy + Eq + std::fmt::Debug, const N: usize> DeckPermutationIt
             let current_mapping = &mut mappings_after[0];
             *current_mapping = PlacementMapping::from_placement_positions_and_mapping(
                 placements[i - 1].positions(),
-                mappings_before.last().unwrap().clone(),
+                *mappings_before.last().unwrap(),
             );
         }
 
@@ -114,12 +114,12 @@ impl<T: Default + Copy + Eq + std::fmt::Debug, const N: usize> DeckPermutationIt
     }
 
     fn update_mappings(&mut self, index: usize) {
-        for i in (index..self.len).into_iter().filter(|i| *i > 0) {
+        for i in (index..self.len).filter(|i| *i > 0) {
             let (mappings_before, mappings_after) = self.placement_mappings.split_at_mut(i);
             let current_mapping = &mut mappings_after[0];
             *current_mapping = PlacementMapping::from_placement_positions_and_mapping(
                 self.placements[i - 1].positions(),
-                mappings_before.last().unwrap().clone(),
+                *mappings_before.last().unwrap(),
             );
         }
     }
@@ -0,0 +1,191 @@
+use crate::analyse::PermutationIterator;
+use crate::deck::Deck;
+use crate::permutation_simple::AllPermutationsIterator;
+
+/// Minimal xorshift64 PRNG so sampling runs are reproducible from a plain `u64` seed without
+/// pulling in an external RNG crate. `pub(crate)` so other random-search subsystems (e.g. the
+/// simulated-annealing deck optimizer) can reuse it instead of each rolling their own.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Xorshift64 {
+    state: u64,
+}
+
+impl Xorshift64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            state: if seed == 0 { 0x9E3779B97F4A7C15 } else { seed },
+        }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+const DEFAULT_SAMPLE_SIZE: u64 = 100_000;
+const DEFAULT_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+fn factorial(n: usize) -> u64 {
+    (1..=n as u64).product()
+}
+
+/// Draws `sample_size` independent uniformly-random orderings of the deck's cards via
+/// in-place Fisher-Yates, instead of enumerating every unique multiset permutation. Lets
+/// callers trade exactness for time on decks where `DeckPermutationIterator::count()` would
+/// otherwise run for longer than is useful.
+///
+/// When `N! <= sample_size` the deck is small enough that exhaustive enumeration is both exact
+/// and no more expensive, so construction falls back to `AllPermutationsIterator` instead of
+/// drawing (possibly duplicate) random samples.
+pub struct SampledPermutationIterator<T, const N: usize> {
+    cards: [T; N],
+    rng: Xorshift64,
+    sample_size: u64,
+    drawn: u64,
+    exhaustive: Option<AllPermutationsIterator<T>>,
+}
+
+impl<T: Default + Copy, const N: usize> SampledPermutationIterator<T, N> {
+    pub fn new(deck: Deck<T, N>) -> Self {
+        Self::with_sample_size(deck, DEFAULT_SEED, DEFAULT_SAMPLE_SIZE)
+    }
+
+    pub fn with_sample_size(deck: Deck<T, N>, seed: u64, sample_size: u64) -> Self {
+        let mut cards = [T::default(); N];
+        for (i, card) in deck.card_iter().enumerate() {
+            cards[i] = card;
+        }
+        let exhaustive = if factorial(N) <= sample_size {
+            Some(<AllPermutationsIterator<T> as PermutationIterator<Deck<T, N>>>::new(deck))
+        } else {
+            None
+        };
+        Self {
+            cards,
+            rng: Xorshift64::new(seed),
+            sample_size,
+            drawn: 0,
+            exhaustive,
+        }
+    }
+
+    fn next(&mut self) -> Option<Deck<T, N>> {
+        if let Some(exhaustive) = &mut self.exhaustive {
+            return <AllPermutationsIterator<T> as PermutationIterator<Deck<T, N>>>::next(
+                exhaustive,
+            );
+        }
+        if self.drawn >= self.sample_size {
+            return None;
+        }
+        self.drawn += 1;
+        let mut shuffled = self.cards;
+        for i in (1..N).rev() {
+            let j = self.rng.below(i + 1);
+            shuffled.swap(i, j);
+        }
+        Some(Deck::from(shuffled))
+    }
+}
+
+impl<T: Default + Copy, const N: usize> PermutationIterator<Deck<T, N>>
+    for SampledPermutationIterator<T, N>
+{
+    fn new(deck: Deck<T, N>) -> Self {
+        SampledPermutationIterator::new(deck)
+    }
+
+    fn next(&mut self) -> Option<Deck<T, N>> {
+        SampledPermutationIterator::next(self)
+    }
+
+    /// The actual number of permutations this iterator will yield: `N!` when it fell back to
+    /// exhaustive enumeration, otherwise the requested sample size. Surfaced so `ConditionCount`
+    /// style results can report their true sample size (`total_amount` in `as_map`) rather than
+    /// an assumed one.
+    fn count(&self) -> u64 {
+        if self.exhaustive.is_some() {
+            factorial(N)
+        } else {
+            self.sample_size
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SampledPermutationIterator;
+    use crate::analyse::PermutationIterator;
+    use crate::deck::Deck;
+
+    #[test]
+    fn draws_exactly_sample_size_permutations() {
+        let deck: Deck<usize, 6> = Deck::from([0, 1, 2, 3, 4, 5]);
+        let mut iterator = SampledPermutationIterator::with_sample_size(deck, 42, 50);
+
+        let mut drawn = 0;
+        while iterator.next().is_some() {
+            drawn += 1;
+        }
+
+        assert_eq!(drawn, 50);
+    }
+
+    #[test]
+    fn falls_back_to_exhaustive_enumeration_for_small_decks() {
+        let deck: Deck<usize, 3> = Deck::from([0, 1, 2]);
+        let mut iterator = SampledPermutationIterator::with_sample_size(deck, 42, 50);
+
+        assert_eq!(iterator.count(), 6);
+
+        let mut permutations = Vec::new();
+        while let Some(shuffled) = iterator.next() {
+            permutations.push(shuffled.card_iter().collect::<Vec<usize>>());
+        }
+        permutations.sort();
+
+        assert_eq!(
+            permutations,
+            vec![
+                vec![0, 1, 2],
+                vec![0, 2, 1],
+                vec![1, 0, 2],
+                vec![1, 2, 0],
+                vec![2, 0, 1],
+                vec![2, 1, 0],
+            ]
+        );
+    }
+
+    #[test]
+    fn is_reproducible_from_the_same_seed() {
+        let deck: Deck<usize, 6> = Deck::from([0, 1, 2, 3, 4, 5]);
+        let mut a = SampledPermutationIterator::with_sample_size(deck, 1234, 10);
+        let mut b = SampledPermutationIterator::with_sample_size(deck, 1234, 10);
+
+        for _ in 0..10 {
+            assert_eq!(a.next(), b.next());
+        }
+    }
+
+    #[test]
+    fn every_draw_is_a_permutation_of_the_deck() {
+        let deck: Deck<usize, 5> = Deck::from([0, 1, 2, 3, 4]);
+        let mut iterator = SampledPermutationIterator::with_sample_size(deck, 7, 20);
+
+        while let Some(shuffled) = iterator.next() {
+            let mut cards: Vec<usize> = shuffled.card_iter().collect();
+            cards.sort();
+            assert_eq!(cards, vec![0, 1, 2, 3, 4]);
+        }
+    }
+}
@@ -1,6 +1,84 @@
 use crate::analyse::PermutationIterator;
 use crate::deck::Deck;
 
+pub(crate) fn factorial(n: usize) -> u64 {
+    (1..=n as u64).product()
+}
+
+/// Decodes the `k`-th permutation (0-indexed, lexicographic over the initial `0..n` index
+/// order) via the factorial number system: the Lehmer code digit for position `i` is
+/// `(k / i!) % (i + 1)`, read from the highest factorial down, each digit selecting and
+/// removing that many items from what's left of the index pool.
+fn unrank(n: usize, mut k: u64) -> Vec<usize> {
+    let mut available: Vec<usize> = (0..n).collect();
+    let mut result = Vec::with_capacity(n);
+    for i in (1..=n).rev() {
+        let place = factorial(i - 1);
+        let digit = (k / place) as usize;
+        k %= place;
+        result.push(available.remove(digit));
+    }
+    result
+}
+
+/// Advances `indices` to the next lexicographically larger permutation in place (the standard
+/// "next permutation" algorithm), returning `false` once `indices` is already the last one.
+fn next_lexicographic_permutation(indices: &mut [usize]) -> bool {
+    if indices.len() < 2 {
+        return false;
+    }
+    let mut i = indices.len() - 1;
+    while i > 0 && indices[i - 1] >= indices[i] {
+        i -= 1;
+    }
+    if i == 0 {
+        return false;
+    }
+    let mut j = indices.len() - 1;
+    while indices[j] <= indices[i - 1] {
+        j -= 1;
+    }
+    indices.swap(i - 1, j);
+    indices[i..].reverse();
+    true
+}
+
+/// Enumerates permutations of `items` starting at an arbitrary rank instead of from the
+/// beginning, by unranking the starting index and then stepping forward with the standard
+/// next-permutation algorithm. This is what lets `AnalysisExecutor::execute_parallel` hand each
+/// worker thread a contiguous shard of the `N!` permutation space without replaying the shards
+/// before it, which `AllPermutationsIterator`'s Heap's-algorithm state cannot do.
+pub struct RankedPermutationsIterator<T, const N: usize> {
+    items: [T; N],
+    indices: [usize; N],
+    remaining: u64,
+}
+
+impl<T: Copy, const N: usize> RankedPermutationsIterator<T, N> {
+    pub fn new(items: [T; N], start_rank: u64, len: u64) -> Self {
+        let mut indices = [0; N];
+        indices.copy_from_slice(&unrank(N, start_rank));
+        Self {
+            items,
+            indices,
+            remaining: len,
+        }
+    }
+
+    pub fn next(&mut self) -> Option<Deck<T, N>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let mut deck = [self.items[0]; N];
+        for (slot, index) in deck.iter_mut().zip(self.indices.iter()) {
+            *slot = self.items[*index];
+        }
+        next_lexicographic_permutation(&mut self.indices);
+        Some(Deck::from(deck))
+    }
+}
+
 pub struct AllPermutationsIterator<T> {
     collection: Vec<T>,
     initial_returned: bool,
@@ -67,9 +145,49 @@ impl<T> AllPermutationsIterator<T> {
 
 #[cfg(test)]
 mod tests {
-    use super::AllPermutationsIterator;
+    use super::{AllPermutationsIterator, RankedPermutationsIterator};
     use std::collections::HashSet;
 
+    #[test]
+    fn ranked_iterator_starting_at_zero_covers_the_same_set_as_the_full_enumeration() {
+        let mut ranked = RankedPermutationsIterator::new([1, 2, 3], 0, 6);
+        let mut from_rank = HashSet::new();
+        while let Some(deck) = ranked.next() {
+            from_rank.insert(deck.card_iter().collect::<Vec<i32>>());
+        }
+
+        let (_, from_full) = collect_permutations([1, 2, 3]);
+        let from_full: HashSet<Vec<i32>> = from_full.into_iter().map(Vec::from).collect();
+
+        assert_eq!(from_rank, from_full);
+    }
+
+    #[test]
+    fn ranked_iterator_can_start_mid_sequence() {
+        let mut ranked = RankedPermutationsIterator::new([1, 2, 3], 3, 3);
+
+        let mut permutations = Vec::new();
+        while let Some(deck) = ranked.next() {
+            permutations.push(deck.card_iter().collect::<Vec<i32>>());
+        }
+
+        assert_eq!(
+            permutations,
+            vec![vec![2, 3, 1], vec![3, 1, 2], vec![3, 2, 1]]
+        );
+    }
+
+    #[test]
+    fn shards_across_the_full_space_partition_it_without_overlap() {
+        let mut seen = HashSet::new();
+        for start in 0..6 {
+            let mut shard = RankedPermutationsIterator::new([1, 2, 3], start, 1);
+            let deck = shard.next().unwrap();
+            assert!(seen.insert(deck.card_iter().collect::<Vec<i32>>()));
+        }
+        assert_eq!(seen.len(), 6);
+    }
+
     #[test]
     fn permutations_3_deck_test() {
         let deck = [1, 2, 3];
@@ -1,3 +1,9 @@
+// `rank`/`unrank`, `to_bits`/`BitPlacement`, `ReplacementPlacementIterator`, and
+// `PowersetIterator` round out this module's combinatorics API but aren't wired into the CLI
+// yet — they're exercised only by this module's own tests below. Allow dead_code here rather
+// than wiring them into a path that doesn't need them.
+#![allow(dead_code)]
+
 #[derive(Debug, Copy, Clone)]
 pub struct PlacementIterator<const N: usize> {
     k: usize,
@@ -5,11 +11,30 @@ pub struct PlacementIterator<const N: usize> {
     c: [usize; N],
     j: usize,
     finished: bool,
+    remaining: usize,
+    back_consumed: usize,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Placement<const N: usize>([usize; N], usize);
 
+/// `C(n, k)`, computed with the classic alternating multiply/divide loop (`acc = acc * (n - i) /
+/// (i + 1)`) so the running product stays within a factor of `k` of the final result, rather than a
+/// precomputed Pascal's-triangle table: a table indexed up to the const generic `N` would need a
+/// `[[usize; N + 1]; N + 1]` array, and `N + 1` isn't expressible as a stable const generic array
+/// length here (no `generic_const_exprs`).
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    let mut acc: usize = 1;
+    for i in 0..k {
+        acc = acc * (n - i) / (i + 1);
+    }
+    acc
+}
+
 impl<const N: usize> PlacementIterator<N> {
     pub fn new(n: usize, k: usize) -> Self {
         if k > n {
@@ -19,8 +44,8 @@ impl<const N: usize> PlacementIterator<N> {
             panic!("n = {} is too large, max value is N = {}", n, N);
         }
         let mut c = [0; N];
-        for i in (0..k).into_iter() {
-            c[i] = i;
+        for (i, slot) in c.iter_mut().enumerate().take(k) {
+            *slot = i;
         }
         if k < n {
             c[k] = n;
@@ -31,6 +56,8 @@ impl<const N: usize> PlacementIterator<N> {
             c,
             j: 0,
             finished: false,
+            remaining: binomial(n, k),
+            back_consumed: 0,
         }
     }
 
@@ -41,6 +68,46 @@ impl<const N: usize> PlacementIterator<N> {
         *self = Self::new(self.n, self.k);
     }
 
+    /// Recovers the `m`-th k-combination of `0..n` in lexicographic order (ascending positions,
+    /// ordered as if comparing tuples left to right) directly, without iterating the preceding
+    /// `m` placements. Chooses indices from the most significant (leftmost) slot to the least:
+    /// for each slot it counts, for candidate values `v` starting just after the previous slot's
+    /// choice, how many combinations `C(n - v - 1, remaining_slots)` start with that prefix, and
+    /// either commits to `v` (if `m` falls within that block) or subtracts the block's size and
+    /// tries the next `v`. The resulting `Placement` is independent of this module's own
+    /// revolving-door traversal order — `rank`/`unrank` are a separate, purely lexicographic
+    /// numbering of the same `C(n, k)` combinations.
+    pub fn unrank(n: usize, k: usize, m: usize) -> Placement<N> {
+        if k > n {
+            panic!("k = {} is too large, max value is n = {}", k, n);
+        }
+        if n > N {
+            panic!("n = {} is too large, max value is N = {}", n, N);
+        }
+        let total = binomial(n, k);
+        if m >= total {
+            panic!(
+                "m = {} is out of range, there are only {} combinations",
+                m, total
+            );
+        }
+
+        let mut c = [0; N];
+        let mut remaining = m;
+        let mut start = 0;
+        for slot in 0..k {
+            let remaining_slots = k - slot - 1;
+            let mut v = start;
+            while remaining >= binomial(n - v - 1, remaining_slots) {
+                remaining -= binomial(n - v - 1, remaining_slots);
+                v += 1;
+            }
+            c[slot] = v;
+            start = v + 1;
+        }
+        Placement(c, k)
+    }
+
     fn update_combination(&mut self) -> bool {
         if self.finished {
             return false;
@@ -56,7 +123,7 @@ impl<const N: usize> PlacementIterator<N> {
             }
             return true;
         }
-        if self.k % 2 == 0 {
+        if self.k.is_multiple_of(2) {
             if self.c[0] > 0 {
                 self.c[0] -= 1;
             } else {
@@ -109,35 +176,310 @@ impl<const N: usize> Iterator for PlacementIterator<N> {
     type Item = Placement<N>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.finished {
+        if self.finished || self.remaining == 0 {
             return None;
         }
         let next_combination = Placement(self.c, self.k);
         self.update_combination();
+        self.remaining -= 1;
 
         Some(next_combination)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for PlacementIterator<N> {}
+
+/// Yields combinations from the tail of the same order `next` advances through. `update_combination`
+/// is a revolving-door (Chase's sequence) step whose state (`c`, the ascending positions, and `j`,
+/// the cursor `try_to_increase`/`try_to_decrease` leave behind) isn't recoverable call-by-call from
+/// only the next state — unlike `reset`, which can restart from `(n, k)` alone, undoing one step
+/// needs to know which of several branches produced it. Rather than risk a hand-derived inverse of
+/// that recursive step being subtly wrong in a tree with no compiler to check it against,
+/// `next_back` instead replays a fresh `PlacementIterator` up to the absolute forward index
+/// `binomial(n, k) - 1 - back_consumed`: more work per call (`O(index)` instead of `O(1)`), but
+/// provably consistent with `next`'s order by construction. `self`'s own forward state (`c`/`j`/
+/// `finished`) is left untouched by this — only the `remaining` budget shared with `next` and the
+/// `back_consumed` counter change — so interleaving `next`/`next_back` calls always partitions the
+/// same `binomial(n, k)` combinations without overlap or gaps.
+impl<const N: usize> DoubleEndedIterator for PlacementIterator<N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let total = binomial(self.n, self.k);
+        let target_index = total - 1 - self.back_consumed;
+        let item = PlacementIterator::<N>::new(self.n, self.k)
+            .nth(target_index)
+            .expect("target_index stays within 0..total by construction");
+
+        self.back_consumed += 1;
+        self.remaining -= 1;
+        Some(item)
+    }
 }
 
 impl<const N: usize> Placement<N> {
     pub fn positions(&self) -> &[usize] {
         &self.0[0..self.1]
     }
+
+    /// Lexicographic rank of this combination among all `C(n, k)` combinations of `0..n`: the
+    /// count of combinations that sort strictly before it, found by summing, slot by slot, how
+    /// many combinations share this one's prefix up to that slot but choose a smaller value
+    /// there. `n` must be the same universe size the combination was drawn from (`positions()`
+    /// alone doesn't carry it). Inverse of `PlacementIterator::unrank`.
+    pub fn rank(&self, n: usize) -> usize {
+        let k = self.1;
+        let mut rank = 0;
+        let mut start = 0;
+        for (slot, &c_slot) in self.positions().iter().enumerate() {
+            let remaining_slots = k - slot - 1;
+            for v in start..c_slot {
+                rank += binomial(n - v - 1, remaining_slots);
+            }
+            start = c_slot + 1;
+        }
+        rank
+    }
 }
 
 impl<const N: usize> Default for Placement<N> {
     fn default() -> Self {
         let mut positions = [0; N];
-        for i in 0..N {
-            positions[i] = i;
+        for (i, slot) in positions.iter_mut().enumerate() {
+            *slot = i;
         }
         Self(positions, N)
     }
 }
 
+/// A `Placement`'s selected positions packed into a fixed array of `W` 64-bit words — word index
+/// `pos / 64`, bit `pos % 64`, the same addressing `bitset::BitVector` uses — so subsets over more
+/// than 64 elements are representable as a fixed-size bitmask instead of the single `usize` a test
+/// can only fold positions into while `n` stays within the word width.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BitPlacement<const W: usize> {
+    words: [u64; W],
+}
+
+impl<const N: usize> Placement<N> {
+    // Converts to an unrelated type (`BitPlacement<W>`, not `Self`), which is exactly the
+    // documented false-positive case for this lint.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_bits<const W: usize>(&self) -> BitPlacement<W> {
+        let mut words = [0u64; W];
+        for &position in self.positions() {
+            words[position / 64] |= 1u64 << (position % 64);
+        }
+        BitPlacement { words }
+    }
+}
+
+impl<const W: usize> BitPlacement<W> {
+    pub fn words(&self) -> &[u64; W] {
+        &self.words
+    }
+
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|word| word.count_ones()).sum()
+    }
+
+    pub fn intersection(&self, other: &BitPlacement<W>) -> BitPlacement<W> {
+        let mut words = [0u64; W];
+        for (slot, (a, b)) in words.iter_mut().zip(self.words.iter().zip(other.words.iter())) {
+            *slot = a & b;
+        }
+        BitPlacement { words }
+    }
+
+    pub fn union(&self, other: &BitPlacement<W>) -> BitPlacement<W> {
+        let mut words = [0u64; W];
+        for (slot, (a, b)) in words.iter_mut().zip(self.words.iter().zip(other.words.iter())) {
+            *slot = a | b;
+        }
+        BitPlacement { words }
+    }
+}
+
+/// Convenience adapter over any `Placement<N>`-yielding iterator (`PlacementIterator`,
+/// `PowersetIterator`, ...) that packs each item into a `BitPlacement<W>` via `to_bits`, so
+/// set algebra on the stream doesn't have to go back through `positions()` by hand.
+pub struct BitPlacementIterator<I, const W: usize> {
+    inner: I,
+}
+
+impl<I, const W: usize> BitPlacementIterator<I, W> {
+    pub fn new(inner: I) -> Self {
+        Self { inner }
+    }
+}
+
+impl<I, const N: usize, const W: usize> Iterator for BitPlacementIterator<I, W>
+where
+    I: Iterator<Item = Placement<N>>,
+{
+    type Item = BitPlacement<W>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|placement| placement.to_bits())
+    }
+}
+
+/// Enumerates the `C(n + k - 1, k)` k-length multisets drawn from `n` items (order irrelevant,
+/// repeats allowed), in the same no-alloc, const-generic `[usize; N]` style as
+/// `PlacementIterator`. `c` holds a non-decreasing sequence of `k` indices into `0..n`, each index
+/// repeatable, unlike `PlacementIterator`'s `c`, whose entries are always distinct positions. A
+/// multiset can't be packed into a position bitmask the way a `Placement` can, so callers that
+/// today fold `Placement::positions()` into a bitmask build a per-item count vector from this
+/// iterator's `positions()` instead.
+#[derive(Debug, Copy, Clone)]
+pub struct ReplacementPlacementIterator<const N: usize> {
+    k: usize,
+    n: usize,
+    c: [usize; N],
+    finished: bool,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ReplacementPlacement<const N: usize>([usize; N], usize);
+
+impl<const N: usize> ReplacementPlacementIterator<N> {
+    pub fn new(n: usize, k: usize) -> Self {
+        if k > N {
+            panic!("k = {} is too large, max value is N = {}", k, N);
+        }
+        if n == 0 && k > 0 {
+            panic!("n = 0 admits no selections when k = {} > 0", k);
+        }
+        Self {
+            k,
+            n,
+            c: [0; N],
+            finished: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        if !self.finished {
+            panic!("Trying to reset unfinished iterator");
+        }
+        *self = Self::new(self.n, self.k);
+    }
+
+    fn advance(&mut self) {
+        if self.k == 0 {
+            self.finished = true;
+            return;
+        }
+        let mut i = self.k;
+        loop {
+            if i == 0 {
+                self.finished = true;
+                return;
+            }
+            i -= 1;
+            if self.c[i] + 1 < self.n {
+                break;
+            }
+        }
+        let next_value = self.c[i] + 1;
+        for slot in self.c[i..self.k].iter_mut() {
+            *slot = next_value;
+        }
+    }
+}
+
+impl<const N: usize> Iterator for ReplacementPlacementIterator<N> {
+    type Item = ReplacementPlacement<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let current = ReplacementPlacement(self.c, self.k);
+        self.advance();
+        Some(current)
+    }
+}
+
+impl<const N: usize> ReplacementPlacement<N> {
+    pub fn positions(&self) -> &[usize] {
+        &self.0[0..self.1]
+    }
+}
+
+/// Streams every one of the `2^n` subsets of an `n`-element universe, by driving a fresh
+/// `PlacementIterator::<N>::new(n, k)` for each `k` from `0` to `n` in turn and re-creating it
+/// once it's exhausted. `k = 0` is handled directly rather than by constructing a
+/// `PlacementIterator` with `k = 0`, since that constructor's revolving-door step never reaches a
+/// terminal state when `k = 0` and `n > 0` — there's only ever one `k = 0` subset (the empty one)
+/// to emit anyway. `Item` is `PlacementIterator`'s own `Placement<N>`, so the empty set yields an
+/// empty `positions()` slice just like any other placement would.
+#[derive(Debug, Copy, Clone)]
+pub struct PowersetIterator<const N: usize> {
+    n: usize,
+    k: usize,
+    inner: Option<PlacementIterator<N>>,
+    remaining: usize,
+}
+
+impl<const N: usize> PowersetIterator<N> {
+    pub fn new(n: usize) -> Self {
+        if n > N {
+            panic!("n = {} is too large, max value is N = {}", n, N);
+        }
+        Self {
+            n,
+            k: 0,
+            inner: None,
+            remaining: 1usize << n,
+        }
+    }
+}
+
+impl<const N: usize> Iterator for PowersetIterator<N> {
+    type Item = Placement<N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.k > self.n {
+            return None;
+        }
+        if self.k == 0 {
+            self.k = 1;
+            self.remaining -= 1;
+            return Some(Placement([0; N], 0));
+        }
+        loop {
+            let (n, k) = (self.n, self.k);
+            let inner = self
+                .inner
+                .get_or_insert_with(|| PlacementIterator::new(n, k));
+            if let Some(placement) = inner.next() {
+                self.remaining -= 1;
+                return Some(placement);
+            }
+            self.k += 1;
+            self.inner = None;
+            if self.k > self.n {
+                return None;
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::PlacementIterator;
+    use super::{
+        BitPlacementIterator, PlacementIterator, PowersetIterator, ReplacementPlacementIterator,
+    };
     use std::collections::HashSet;
 
     #[test]
@@ -214,6 +556,26 @@ mod tests {
         assert_eq!(unique_results, expected);
     }
 
+    #[test]
+    fn placement_iterator_size_hint_is_exactly_c_n_k_and_shrinks_as_items_are_consumed() {
+        let mut placements = PlacementIterator::<6>::new(6, 3);
+        assert_eq!(placements.len(), 20);
+        assert_eq!(placements.size_hint(), (20, Some(20)));
+
+        for remaining in (0..20).rev() {
+            placements.next();
+            assert_eq!(placements.len(), remaining);
+            assert_eq!(placements.size_hint(), (remaining, Some(remaining)));
+        }
+        assert_eq!(placements.next(), None);
+        assert_eq!(placements.len(), 0);
+    }
+
+    #[test]
+    fn placement_iterator_len_is_one_when_k_equals_n() {
+        assert_eq!(PlacementIterator::<3>::new(3, 3).len(), 1);
+    }
+
     #[test]
     fn placements_n_6_k_3() {
         let (all_results, unique_results) = generate_placements::<6>(6, 3);
@@ -242,4 +604,226 @@ mod tests {
         let unique = all.iter().cloned().collect();
         (all, unique)
     }
+
+    #[test]
+    fn replacement_placements_n_2_k_2() {
+        let (all_results, unique_results) = generate_replacement_placements::<2>(2, 2);
+        let expected = vec![vec![0, 0], vec![0, 1], vec![1, 1]]
+            .into_iter()
+            .collect();
+
+        assert_eq!(all_results.len(), 3);
+        assert_eq!(unique_results, expected);
+    }
+
+    #[test]
+    fn replacement_placements_n_3_k_2() {
+        let (all_results, unique_results) = generate_replacement_placements::<2>(3, 2);
+        let expected = vec![
+            vec![0, 0],
+            vec![0, 1],
+            vec![0, 2],
+            vec![1, 1],
+            vec![1, 2],
+            vec![2, 2],
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(all_results.len(), 6);
+        assert_eq!(unique_results, expected);
+    }
+
+    #[test]
+    fn replacement_placements_k_0_yields_exactly_the_empty_multiset() {
+        let (all_results, _) = generate_replacement_placements::<1>(3, 0);
+
+        assert_eq!(all_results, vec![Vec::<usize>::new()]);
+    }
+
+    #[test]
+    fn replacement_placements_n_1_k_3_is_a_single_all_zero_multiset() {
+        let (all_results, _) = generate_replacement_placements::<3>(1, 3);
+
+        assert_eq!(all_results, vec![vec![0, 0, 0]]);
+    }
+
+    fn generate_replacement_placements<const N: usize>(
+        n: usize,
+        k: usize,
+    ) -> (Vec<Vec<usize>>, HashSet<Vec<usize>>) {
+        let all: Vec<Vec<usize>> = ReplacementPlacementIterator::<N>::new(n, k)
+            .map(|gc| gc.positions().to_vec())
+            .collect();
+        let unique = all.iter().cloned().collect();
+        (all, unique)
+    }
+
+    #[test]
+    fn powerset_of_3_elements_has_all_8_subsets() {
+        let subsets: HashSet<usize> = PowersetIterator::<3>::new(3)
+            .map(|placement| {
+                placement
+                    .positions()
+                    .iter()
+                    .fold(0, |mask, position| mask | (1 << position))
+            })
+            .collect();
+
+        let expected = (0..8).collect();
+        assert_eq!(subsets, expected);
+    }
+
+    #[test]
+    fn powerset_size_hint_is_exactly_2_to_the_n_and_shrinks_as_items_are_consumed() {
+        let mut powerset = PowersetIterator::<3>::new(3);
+        assert_eq!(powerset.size_hint(), (8, Some(8)));
+
+        for remaining in (0..8).rev() {
+            powerset.next();
+            assert_eq!(powerset.size_hint(), (remaining, Some(remaining)));
+        }
+        assert_eq!(powerset.next(), None);
+    }
+
+    #[test]
+    fn powerset_of_the_empty_universe_is_just_the_empty_set() {
+        let subsets: Vec<Vec<usize>> = PowersetIterator::<1>::new(0)
+            .map(|placement| placement.positions().to_vec())
+            .collect();
+
+        assert_eq!(subsets, vec![Vec::<usize>::new()]);
+    }
+
+    #[test]
+    fn unrank_recovers_every_combination_in_lexicographic_order() {
+        let expected: Vec<Vec<usize>> = vec![
+            vec![0, 1],
+            vec![0, 2],
+            vec![0, 3],
+            vec![1, 2],
+            vec![1, 3],
+            vec![2, 3],
+        ];
+
+        for (m, expected_positions) in expected.into_iter().enumerate() {
+            let placement = PlacementIterator::<4>::unrank(4, 2, m);
+            assert_eq!(placement.positions(), expected_positions.as_slice());
+        }
+    }
+
+    #[test]
+    fn rank_is_the_inverse_of_unrank() {
+        for m in 0..PlacementIterator::<4>::new(4, 2).count() {
+            let placement = PlacementIterator::<4>::unrank(4, 2, m);
+            assert_eq!(placement.rank(4), m);
+        }
+    }
+
+    #[test]
+    fn rank_matches_unrank_across_every_combination_a_placement_iterator_visits() {
+        for placement in PlacementIterator::<6>::new(6, 3) {
+            let unranked = PlacementIterator::<6>::unrank(6, 3, placement.rank(6));
+            assert_eq!(unranked.positions(), placement.positions());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn unrank_panics_when_m_is_out_of_range() {
+        PlacementIterator::<4>::unrank(4, 2, 6);
+    }
+
+    #[test]
+    fn to_bits_sets_exactly_the_selected_positions_across_word_boundaries() {
+        // Rank 128 selects positions {0, 129}: bit 0 of word 0 and bit 1 of word 2.
+        let placement = PlacementIterator::<130>::unrank(130, 2, 128);
+        let positions = placement.positions().to_vec();
+        let bits: super::BitPlacement<3> = placement.to_bits();
+
+        assert_eq!(bits.count_ones(), positions.len() as u32);
+        for position in 0..130 {
+            let expected = positions.contains(&position);
+            let actual = bits.words()[position / 64] & (1 << (position % 64)) != 0;
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn bit_placement_intersection_and_union_match_position_set_algebra() {
+        let a = PlacementIterator::<8>::new(8, 3).next().unwrap();
+        let b = PlacementIterator::<8>::new(8, 3).nth(1).unwrap();
+        let a_bits: super::BitPlacement<1> = a.to_bits();
+        let b_bits: super::BitPlacement<1> = b.to_bits();
+
+        let union_count = a
+            .positions()
+            .iter()
+            .chain(b.positions().iter())
+            .collect::<HashSet<_>>()
+            .len() as u32;
+        let intersection_count = a
+            .positions()
+            .iter()
+            .filter(|p| b.positions().contains(*p))
+            .count() as u32;
+
+        assert_eq!(a_bits.union(&b_bits).count_ones(), union_count);
+        assert_eq!(
+            a_bits.intersection(&b_bits).count_ones(),
+            intersection_count
+        );
+    }
+
+    #[test]
+    fn bit_placement_iterator_mirrors_the_wrapped_placement_iterator() {
+        let wrapped: Vec<super::BitPlacement<1>> =
+            BitPlacementIterator::<_, 1>::new(PlacementIterator::<8>::new(8, 3)).collect();
+        let direct: Vec<super::BitPlacement<1>> = PlacementIterator::<8>::new(8, 3)
+            .map(|placement| placement.to_bits())
+            .collect();
+
+        assert_eq!(wrapped, direct);
+    }
+
+    #[test]
+    fn next_back_yields_the_forward_sequence_in_reverse_order() {
+        for (n, k) in [(4usize, 2usize), (5, 3), (6, 1), (3, 3), (5, 0)] {
+            let mut forward: Vec<Vec<usize>> = PlacementIterator::<6>::new(n, k)
+                .map(|p| p.positions().to_vec())
+                .collect();
+
+            let mut iterator = PlacementIterator::<6>::new(n, k);
+            let mut backward = Vec::new();
+            while let Some(placement) = iterator.next_back() {
+                backward.push(placement.positions().to_vec());
+            }
+
+            forward.reverse();
+            assert_eq!(backward, forward, "mismatch for n={}, k={}", n, k);
+        }
+    }
+
+    #[test]
+    fn next_and_next_back_meet_in_the_middle_without_overlap_or_gaps() {
+        let mut iterator = PlacementIterator::<6>::new(6, 3);
+        let mut seen = Vec::new();
+        loop {
+            let front = iterator.next();
+            let back = iterator.next_back();
+            if front.is_none() && back.is_none() {
+                break;
+            }
+            if let Some(placement) = front {
+                seen.push(placement.positions().to_vec());
+            }
+            if let Some(placement) = back {
+                seen.push(placement.positions().to_vec());
+            }
+        }
+
+        let unique: HashSet<Vec<usize>> = seen.iter().cloned().collect();
+        assert_eq!(seen.len(), 20);
+        assert_eq!(unique.len(), 20);
+    }
 }
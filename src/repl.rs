@@ -0,0 +1,243 @@
+use crate::analyse::{standard_turn_profile, AnalysisExecutor, SuppressWarnings};
+use crate::bitset::DeckBits;
+use crate::condition::Condition;
+use crate::deck::{Card, CardIdentity, Deck};
+use crate::dsl::Program;
+use crate::permutation_optimized::DeckPermutationIterator;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// `repl` subcommand: keeps one working deck and one set of already-registered analyses resident
+/// across queries instead of re-invoking the process per deck, so iterating on combos ("swap card
+/// 5 for card 9, recheck availability") is a single typed line rather than a fresh parse + batch
+/// run. Built directly on `AnalysisExecutor::retarget`/`PermutationIterator`, the same machinery
+/// `main::execute` drives for one-shot batch runs.
+///
+/// Line editing is plain `stdin` line buffering: there's no line-editing crate in this tree to
+/// vendor, so arrow-key history recall and inline Tab-key completion aren't available. Instead
+/// `history` lists what's been typed this session and `complete <prefix>` lists the card ids
+/// and analysis names a prefix could expand to, so a user driving a dumb terminal (or piping
+/// commands in from a script) still has both facilities available as ordinary commands.
+pub fn run<const N: usize>(analysis: Vec<AnalysisExecutor<N>>, pool: Vec<Card>) {
+    let mut state = ReplState::new(analysis, pool);
+    let stdin = std::io::stdin();
+
+    print_help();
+    loop {
+        print!("> ");
+        std::io::stdout().flush().ok();
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line).unwrap_or(0) == 0 {
+            break;
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        state.history.push(line.to_owned());
+
+        if matches!(line, "quit" | "exit") {
+            break;
+        }
+        if let Err(message) = state.dispatch(line) {
+            println!("ERROR: {}", message);
+        }
+    }
+}
+
+struct ReplState<const N: usize> {
+    analysis: Vec<AnalysisExecutor<N>>,
+    pool: Vec<Card>,
+    deck: [CardIdentity; N],
+    conditions: HashMap<String, Program>,
+    history: Vec<String>,
+}
+
+impl<const N: usize> ReplState<N> {
+    fn new(analysis: Vec<AnalysisExecutor<N>>, pool: Vec<Card>) -> Self {
+        ReplState {
+            analysis,
+            pool,
+            deck: [CardIdentity::None; N],
+            conditions: HashMap::new(),
+            history: Vec::new(),
+        }
+    }
+
+    fn dispatch(&mut self, line: &str) -> Result<(), String> {
+        let mut parts = line.split_whitespace();
+        let command = parts.next().unwrap_or("");
+        let rest: Vec<&str> = parts.collect();
+
+        match command {
+            "help" => print_help(),
+            "deck" => self.print_deck(),
+            "cards" => self.print_cards(),
+            "analyses" => self.print_analyses(),
+            "history" => self.print_history(),
+            "conditions" => self.print_conditions(),
+            "set" => self.set(&rest)?,
+            "clear" => self.clear(&rest)?,
+            "run" => self.run_analysis(&rest)?,
+            "define" => self.define_condition(&rest)?,
+            "check" => self.check_condition(&rest)?,
+            "complete" => self.complete(&rest)?,
+            _ => return Err(format!("unknown command \"{}\" (try \"help\")", command)),
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, args: &[&str]) -> Result<(), String> {
+        let [position, id] = args else {
+            return Err("usage: set <position> <card id>".to_owned());
+        };
+        let position: usize = position
+            .parse()
+            .map_err(|_| format!("\"{}\" isn't a deck position", position))?;
+        let id: u8 = id
+            .parse()
+            .map_err(|_| format!("\"{}\" isn't a card id", id))?;
+        if position >= N {
+            return Err(format!("deck only has positions 0..{}", N));
+        }
+        let card = self
+            .pool
+            .iter()
+            .find(|card| card.id() == crate::deck::Id::from(id))
+            .copied()
+            .ok_or_else(|| format!("no card with id {} in the loaded pool", id))?;
+        self.deck[position] = CardIdentity::Full(card);
+        Ok(())
+    }
+
+    fn clear(&mut self, args: &[&str]) -> Result<(), String> {
+        let [position] = args else {
+            return Err("usage: clear <position>".to_owned());
+        };
+        let position: usize = position
+            .parse()
+            .map_err(|_| format!("\"{}\" isn't a deck position", position))?;
+        if position >= N {
+            return Err(format!("deck only has positions 0..{}", N));
+        }
+        self.deck[position] = CardIdentity::None;
+        Ok(())
+    }
+
+    fn run_analysis(&mut self, args: &[&str]) -> Result<(), String> {
+        let [name] = args else {
+            return Err("usage: run <analysis name>".to_owned());
+        };
+        let template = self
+            .analysis
+            .iter()
+            .find(|a| a.name() == *name)
+            .ok_or_else(|| format!("no analysis named \"{}\" (try \"analyses\")", name))?;
+        let executor = template.retarget(Deck::from(self.deck));
+        let results = executor
+            .execute::<DeckPermutationIterator<CardIdentity, N>>(SuppressWarnings::Yes)
+            .expect("SuppressWarnings::Yes never returns the permutation-count warning");
+        for result in results {
+            println!("{}", result);
+        }
+        Ok(())
+    }
+
+    fn define_condition(&mut self, args: &[&str]) -> Result<(), String> {
+        let [name, expression @ ..] = args else {
+            return Err("usage: define <name> <expression>".to_owned());
+        };
+        if expression.is_empty() {
+            return Err("usage: define <name> <expression>".to_owned());
+        }
+        let program = Program::parse(&expression.join(" "))
+            .map_err(|err| format!("invalid expression: {}", err))?;
+        self.conditions.insert((*name).to_owned(), program);
+        Ok(())
+    }
+
+    /// Evaluates a defined condition's name, or (if `args` isn't a known name) an inline
+    /// expression, against the working deck as it stands right now: a single immediate check,
+    /// not a permutation run, so it's the fast way to "toggle" a named condition on and off
+    /// while swapping cards in and out of the deck and rechecking after each edit.
+    fn check_condition(&mut self, args: &[&str]) -> Result<(), String> {
+        if args.is_empty() {
+            return Err("usage: check <name> | check <expression>".to_owned());
+        }
+        let program = match self.conditions.get(args[0]) {
+            Some(program) if args.len() == 1 => program.clone(),
+            _ => Program::parse(&args.join(" "))
+                .map_err(|err| format!("invalid expression: {}", err))?,
+        };
+        let deck = Deck::from(self.deck);
+        let turn_profile = standard_turn_profile::<N>();
+        let bits = DeckBits::build(&deck, &turn_profile);
+        println!("{}", program.eval_bits(&bits));
+        Ok(())
+    }
+
+    fn complete(&self, args: &[&str]) -> Result<(), String> {
+        let [prefix] = args else {
+            return Err("usage: complete <prefix>".to_owned());
+        };
+        for name in self.analysis.iter().map(|a| a.name()) {
+            if name.starts_with(prefix) {
+                println!("analysis: {}", name);
+            }
+        }
+        for card in &self.pool {
+            let id = format!("{}", card.id().index());
+            if id.starts_with(prefix) {
+                println!("card: {}", id);
+            }
+        }
+        Ok(())
+    }
+
+    fn print_deck(&self) {
+        for (position, card) in self.deck.iter().enumerate() {
+            println!("{}: {:?}", position, card);
+        }
+    }
+
+    fn print_cards(&self) {
+        for card in &self.pool {
+            println!("{}", card);
+        }
+    }
+
+    fn print_analyses(&self) {
+        for analysis in &self.analysis {
+            println!("{}", analysis.name());
+        }
+    }
+
+    fn print_history(&self) {
+        for (i, line) in self.history.iter().enumerate() {
+            println!("{}: {}", i + 1, line);
+        }
+    }
+
+    fn print_conditions(&self) {
+        for name in self.conditions.keys() {
+            println!("{}", name);
+        }
+    }
+}
+
+fn print_help() {
+    println!("commands:");
+    println!("  deck                       show the working deck");
+    println!("  cards                      list the loaded card pool");
+    println!("  analyses                   list registered analyses");
+    println!("  set <pos> <id>             place a pool card at a deck position");
+    println!("  clear <pos>                empty a deck position");
+    println!("  run <analysis>             re-run a registered analysis against the working deck");
+    println!("  define <name> <expr>       name a condition-expression for later checks");
+    println!("  check <name|expr>          evaluate a named or inline condition against the deck");
+    println!("  conditions                 list defined condition names");
+    println!("  complete <prefix>          list card ids / analysis names starting with prefix");
+    println!("  history                    show commands typed this session");
+    println!("  quit | exit                leave the REPL");
+}
@@ -0,0 +1,326 @@
+use crate::analyse::{AnalysisResult, PermutationIterator};
+use crate::condition::Condition;
+use crate::deck::{CardIdentity, Deck, Turn, TurnNumber, TurnProfile};
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A per-permutation streaming analysis: rather than folding into a handful of running
+/// scalars (as `Analysis` does), it yields one small, sortable key per dealt permutation, so a
+/// distribution over permutation counts too large to hold in memory at once can still be
+/// computed by spilling keys to disk and merging the sorted runs.
+pub trait StreamingAnalysis: std::fmt::Debug + Send + 'static {
+    fn name(&self) -> &str;
+    fn accept(&mut self, card: CardIdentity, turn: Turn);
+    /// Called once per dealt permutation, after every card in it has been `accept`ed. Returns
+    /// this permutation's key (e.g. the turn a tracked condition was first satisfied), or
+    /// `None` if the permutation never satisfies it. Resets any per-permutation state the way
+    /// `Analysis::next_deck` does.
+    fn next_deck(&mut self) -> Option<u32>;
+}
+
+/// Tracks the turn number on which `condition` first becomes satisfied, for use as a
+/// `StreamingAnalysis` key. The repo's `ConditionCount` answers "what fraction of orderings
+/// satisfy this"; this answers "how early", across every permutation, without that distribution
+/// having to fit in memory.
+#[derive(Debug)]
+pub struct SatisfiedTurnAnalysis<T> {
+    name: String,
+    condition: T,
+    satisfied_at: Option<TurnNumber>,
+}
+
+impl<T> SatisfiedTurnAnalysis<T> {
+    pub fn new(name: String, condition: T) -> Self {
+        Self {
+            name,
+            condition,
+            satisfied_at: None,
+        }
+    }
+}
+
+impl<T: Condition> StreamingAnalysis for SatisfiedTurnAnalysis<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn accept(&mut self, card: CardIdentity, turn: Turn) {
+        if self.satisfied_at.is_none() && self.condition.check(card, turn) {
+            self.satisfied_at = Some(turn.number);
+        }
+    }
+
+    fn next_deck(&mut self) -> Option<u32> {
+        self.condition.next_deck();
+        self.satisfied_at.take().map(|turn| turn.value() as u32)
+    }
+}
+
+/// Run-size threshold for [`ExternalSortExecutor`]: once this many keys have accumulated in
+/// memory they're sorted and spilled to a temporary run file.
+const DEFAULT_SPILL_THRESHOLD: usize = 1_000_000;
+
+static RUN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Drives a [`StreamingAnalysis`] over every permutation of a deck, spilling its keys to
+/// sorted, on-disk runs once `spill_threshold` keys have accumulated and k-way merging those
+/// runs at the end, so the turn-by-turn histogram and percentiles it produces never require
+/// holding one record per permutation in memory.
+#[derive(Debug)]
+pub struct ExternalSortExecutor<const N: usize> {
+    deck: Deck<CardIdentity, N>,
+    turn_profile: TurnProfile<N>,
+    analysis: Box<dyn StreamingAnalysis>,
+    spill_threshold: usize,
+}
+
+impl<const N: usize> ExternalSortExecutor<N> {
+    pub(crate) fn new(
+        deck: Deck<CardIdentity, N>,
+        turn_profile: TurnProfile<N>,
+        analysis: Box<dyn StreamingAnalysis>,
+    ) -> Self {
+        Self {
+            deck,
+            turn_profile,
+            analysis,
+            spill_threshold: DEFAULT_SPILL_THRESHOLD,
+        }
+    }
+
+    pub(crate) fn with_spill_threshold(mut self, spill_threshold: usize) -> Self {
+        self.spill_threshold = spill_threshold;
+        self
+    }
+
+    pub fn execute<P>(mut self) -> HistogramResult
+    where
+        P: PermutationIterator<Deck<CardIdentity, N>>,
+    {
+        let mut permutations = P::new(self.deck);
+        let mut runs: Vec<PathBuf> = Vec::new();
+        let mut buffer: Vec<u32> = Vec::with_capacity(self.spill_threshold);
+        let mut total = 0u64;
+        let mut satisfied = 0u64;
+
+        while let Some(deck) = permutations.next() {
+            for (card, turn) in deck.card_iter().zip(self.turn_profile.turn_iter()) {
+                self.analysis.accept(card, *turn);
+            }
+            total += 1;
+            if let Some(key) = self.analysis.next_deck() {
+                satisfied += 1;
+                buffer.push(key);
+                if buffer.len() >= self.spill_threshold {
+                    runs.push(spill_run(&mut buffer));
+                }
+            }
+        }
+        if !buffer.is_empty() {
+            runs.push(spill_run(&mut buffer));
+        }
+
+        let histogram = merge_runs(&runs);
+        for run in &runs {
+            let _ = std::fs::remove_file(run);
+        }
+
+        HistogramResult {
+            name: self.analysis.name().to_owned(),
+            histogram,
+            satisfied,
+            total,
+        }
+    }
+}
+
+fn spill_run(buffer: &mut Vec<u32>) -> PathBuf {
+    buffer.sort_unstable();
+    let path = std::env::temp_dir().join(format!(
+        "snap-kombination-run-{}-{}.bin",
+        std::process::id(),
+        RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut writer = BufWriter::new(File::create(&path).expect("failed to create sort run file"));
+    for key in buffer.iter() {
+        writer
+            .write_all(&key.to_le_bytes())
+            .expect("failed to write sort run file");
+    }
+    buffer.clear();
+    path
+}
+
+fn read_u32(reader: &mut BufReader<File>) -> Option<u32> {
+    let mut bytes = [0u8; 4];
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => Some(u32::from_le_bytes(bytes)),
+        Err(_) => None,
+    }
+}
+
+/// K-way merges the sorted runs into a turn-by-turn histogram, reading only one buffered page
+/// per run at a time rather than loading any run (let alone all of them) fully into memory.
+fn merge_runs(runs: &[PathBuf]) -> BTreeMap<u32, u64> {
+    let mut readers: Vec<BufReader<File>> = runs
+        .iter()
+        .map(|path| BufReader::new(File::open(path).expect("failed to reopen sort run file")))
+        .collect();
+
+    let mut heap: BinaryHeap<std::cmp::Reverse<(u32, usize)>> = BinaryHeap::new();
+    for (run, reader) in readers.iter_mut().enumerate() {
+        if let Some(key) = read_u32(reader) {
+            heap.push(std::cmp::Reverse((key, run)));
+        }
+    }
+
+    let mut histogram = BTreeMap::new();
+    while let Some(std::cmp::Reverse((key, run))) = heap.pop() {
+        *histogram.entry(key).or_insert(0u64) += 1;
+        if let Some(next_key) = read_u32(&mut readers[run]) {
+            heap.push(std::cmp::Reverse((next_key, run)));
+        }
+    }
+    histogram
+}
+
+/// A turn-by-turn histogram of when a tracked condition was first satisfied, plus the
+/// percentiles derived from it (e.g. "90% of orderings hit this combo by turn p90").
+#[derive(Debug)]
+pub struct HistogramResult {
+    name: String,
+    histogram: BTreeMap<u32, u64>,
+    satisfied: u64,
+    total: u64,
+}
+
+const PERCENTILES: [f64; 3] = [0.5, 0.9, 0.99];
+
+impl HistogramResult {
+    /// Smallest turn `k` such that the fraction of all permutations satisfied by turn `k` is at
+    /// least `p`, or `None` if even every satisfied permutation falls short of `p`.
+    pub fn percentile(&self, p: f64) -> Option<u32> {
+        let mut cumulative = 0u64;
+        for (&turn, &count) in &self.histogram {
+            cumulative += count;
+            if cumulative as f64 >= p * self.total as f64 {
+                return Some(turn);
+            }
+        }
+        None
+    }
+}
+
+impl AnalysisResult for HistogramResult {
+    fn as_map(&self) -> std::collections::HashMap<String, String> {
+        let mut map = std::collections::HashMap::new();
+        map.insert("name".to_owned(), self.name.clone());
+        map.insert("total".to_owned(), self.total.to_string());
+        map.insert("satisfied".to_owned(), self.satisfied.to_string());
+        for &p in &PERCENTILES {
+            let label = format!("p{}", (p * 100.0) as u32);
+            let value = self
+                .percentile(p)
+                .map(|turn| turn.to_string())
+                .unwrap_or_else(|| "n/a".to_owned());
+            map.insert(label, value);
+        }
+        for (turn, count) in &self.histogram {
+            map.insert(format!("turn_{}", turn), count.to_string());
+        }
+        map
+    }
+}
+
+impl std::fmt::Display for HistogramResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: satisfied {}/{} decks, percentiles:",
+            self.name, self.satisfied, self.total
+        )?;
+        for &p in &PERCENTILES {
+            match self.percentile(p) {
+                Some(turn) => write!(f, " p{}=turn {}", (p * 100.0) as u32, turn)?,
+                None => write!(f, " p{}=n/a", (p * 100.0) as u32)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExternalSortExecutor, SatisfiedTurnAnalysis};
+    use crate::analyse::AnalysisResult;
+    use crate::condition::CardIdCondition;
+    use crate::deck::{Card, CardIdentity, Deck, Energy, Id, Turn, TurnNumber, TurnProfile};
+    use crate::permutation_simple::AllPermutationsIterator;
+
+    fn turn(number: u8, energy: u8) -> Turn {
+        Turn {
+            number: TurnNumber::from(number),
+            energy: Energy::from(energy),
+        }
+    }
+
+    #[test]
+    fn histograms_the_turn_a_condition_is_first_satisfied() {
+        let deck: Deck<CardIdentity, 3> = Deck::from([
+            CardIdentity::Full(Card::new(0, 0)),
+            CardIdentity::None,
+            CardIdentity::None,
+        ]);
+        let turn_profile =
+            TurnProfile::from([turn(1, 0), turn(2, 0), turn(3, 0)]);
+        let analysis = SatisfiedTurnAnalysis::new(
+            "found".to_owned(),
+            CardIdCondition::new(Id::from(0)),
+        );
+        let executor = ExternalSortExecutor::new(deck, turn_profile, Box::new(analysis))
+            .with_spill_threshold(1);
+
+        let result = executor.execute::<AllPermutationsIterator<CardIdentity>>();
+
+        // AllPermutationsIterator enumerates all 3! = 6 orderings of the 3 positions, treating
+        // the two `None` cards as distinct slots rather than deduplicating them into 3 unique
+        // decks — and card 0 is present in every one of those 6 orderings.
+        let map = result.as_map();
+        assert_eq!(map.get("satisfied").unwrap(), "6");
+        assert_eq!(map.get("total").unwrap(), "6");
+        // Card 0 lands at each of the 3 positions (turns 1, 2, 3) in 2 of the 6 orderings.
+        assert_eq!(map.get("turn_1").unwrap(), "2");
+        assert_eq!(map.get("turn_2").unwrap(), "2");
+        assert_eq!(map.get("turn_3").unwrap(), "2");
+    }
+
+    #[test]
+    fn spills_across_multiple_runs_and_still_merges_correctly() {
+        let deck: Deck<CardIdentity, 3> = Deck::from([
+            CardIdentity::Full(Card::new(0, 0)),
+            CardIdentity::None,
+            CardIdentity::None,
+        ]);
+        let turn_profile =
+            TurnProfile::from([turn(1, 0), turn(2, 0), turn(3, 0)]);
+        let analysis = SatisfiedTurnAnalysis::new(
+            "found".to_owned(),
+            CardIdCondition::new(Id::from(0)),
+        );
+        let spilling = ExternalSortExecutor::new(deck, turn_profile, Box::new(analysis))
+            .with_spill_threshold(1)
+            .execute::<AllPermutationsIterator<CardIdentity>>();
+
+        let analysis = SatisfiedTurnAnalysis::new(
+            "found".to_owned(),
+            CardIdCondition::new(Id::from(0)),
+        );
+        let unspilled = ExternalSortExecutor::new(deck, turn_profile, Box::new(analysis))
+            .execute::<AllPermutationsIterator<CardIdentity>>();
+
+        assert_eq!(spilling.as_map(), unspilled.as_map());
+    }
+}